@@ -2,25 +2,36 @@ use std::{
     env::{self, current_exe},
     fs::{self, File},
     io::{self, Cursor},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
 
+use crate::{
+    config::{Profile, CLIENTFILENAMEPREFIX, LOCALAPPDATA_NAME, NAME, SETUP, URI, YEARS},
+    cancel, clients,
+    error::{Context, Error, ErrorKind, Result},
+    launcher,
+    lock::InstallLock,
+    mods,
+    output::{self, Event},
+    progress::Progress,
+    settings::Settings,
+    uninstall, utils,
+};
 #[cfg(target_os = "linux")]
-use std::process::Command;
-
-use crate::{config::*, utils};
-use anyhow::{anyhow, Context, Result};
+use crate::{config::MIN_WINE_VERSION, dxvk, prerequisites, wine::{self, Runner}};
 use futures_util::StreamExt;
-use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::Client;
 use zip::ZipArchive;
 
 const MAX_RETRIES: u32 = 6;
 const RETRY_DELAY: Duration = Duration::from_secs(2);
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Added to client folders while they are being extracted.
+const PARTIAL_SUFFIX: &str = ".partial";
 
-fn build_client() -> Result<Client> {
+pub fn build_client() -> Result<Client> {
     Client::builder()
         .connect_timeout(CONNECT_TIMEOUT)
         .tcp_keepalive(Duration::from_secs(10))
@@ -42,11 +53,21 @@ pub fn get_install_dir() -> Result<PathBuf> {
     }
 }
 
-async fn fetch_latest_version(client: &Client) -> Result<String> {
+/// Where the bootstrapper copies itself, the URI handler points to it.
+pub fn get_launcher_path() -> Result<PathBuf> {
+    #[cfg(windows)]
+    let name = "launcher.exe";
+    #[cfg(target_os = "linux")]
+    let name = "launcher";
+    Ok(get_install_dir()?.join(name))
+}
+
+pub async fn fetch_latest_version(client: &Client) -> Result<String> {
     let version = client
         .get(format!("{SETUP}/version"))
         .send()
         .await
+        .inspect(|res| output::debug!("GET {SETUP}/version: {}", res.status()))
         .context("Failed to reach version endpoint")?
         .error_for_status()
         .context("Version endpoint returned error")?
@@ -57,20 +78,26 @@ async fn fetch_latest_version(client: &Client) -> Result<String> {
         .to_string();
 
     if version.is_empty() {
-        return Err(anyhow!("Server returned an empty version string"));
+        return Err(Error::new(ErrorKind::Network, "Server returned an empty version string"));
     }
 
     Ok(version)
 }
 
+/// The version of the installed clients, `None` before the first install.
+pub fn installed_version() -> Option<String> {
+    fs::read_to_string(get_install_dir().ok()?.join("version"))
+        .ok()
+        .map(|version| version.trim().to_string())
+}
+
 pub async fn is_up_to_update() -> Result<(bool, String)> {
     let client = Client::new();
     let install_dir = get_install_dir()?;
     let latest_version = fetch_latest_version(&client).await?;
 
     let up_to_date = fs::read_to_string(install_dir.join("version"))
-        .map(|v| v.trim() == latest_version)
-        .unwrap_or(false);
+        .is_ok_and(|v| v.trim() == latest_version);
 
     Ok((up_to_date, latest_version))
 }
@@ -78,19 +105,27 @@ pub async fn is_up_to_update() -> Result<(bool, String)> {
 /// Downloads a URL into a `Vec<u8>` with retries and a progress bar.
 /// Falls back gracefully if content-length is not provided.
 /// refactor from claude (im too dumb)
-async fn download_with_retry(client: &Client, url: &str, label: &str) -> Result<Vec<u8>> {
-    let mut last_err = anyhow!("No attempts made");
+pub async fn download_with_retry(client: &Client, url: &str, label: &str) -> Result<Vec<u8>> {
+    let mut last_err = Error::new(ErrorKind::Network, "No attempts made");
 
     for attempt in 1..=MAX_RETRIES {
         if attempt > 1 {
-            paris::warn!("Retry {}/{MAX_RETRIES} for {label}...", attempt);
-            tokio::time::sleep(RETRY_DELAY).await;
+            output::warn!("Retry {}/{MAX_RETRIES} for {label}...", attempt);
+            tokio::select! {
+                () = tokio::time::sleep(RETRY_DELAY) => {}
+                () = cancel::cancelled() => return Err(cancel::error()),
+            }
         }
 
-        match try_download(client, url, label).await {
+        let result = tokio::select! {
+            result = try_download(client, url, label) => result,
+            () = cancel::cancelled() => return Err(cancel::error()),
+        };
+        match result {
             Ok(bytes) => return Ok(bytes),
             Err(e) => {
-                paris::error!("Download attempt {attempt} failed: {e}");
+                output::error!("Download attempt {attempt} failed: {e}");
+                output::debug!("Attempt {attempt} for {url}: {e:#}");
                 last_err = e;
             }
         }
@@ -104,74 +139,75 @@ async fn try_download(client: &Client, url: &str, label: &str) -> Result<Vec<u8>
         .get(url)
         .send()
         .await
+        .inspect(|res| output::debug!("GET {url}: {}", res.status()))
         .context("Failed to send request")?
         .error_for_status()
         .context("Server returned error status")?;
 
     let total_size = res.content_length(); // Optional — not required
 
-    let pb = ProgressBar::new(total_size.unwrap_or(0));
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{msg}\n{percent_precise}% |{bar}| {bytes}/{total_bytes} [{elapsed_precise}<{eta_precise}, {decimal_bytes_per_sec}]\n")?
-            .progress_chars("█▌ "),
-    );
-    pb.set_message(format!("Downloading {label}..."));
+    output::emit(&Event::DownloadProgress { label, downloaded: 0, total: total_size });
 
     let mut body = match total_size {
-        Some(size) => Vec::with_capacity(size as usize),
+        Some(size) => Vec::with_capacity(usize::try_from(size).unwrap_or_default()),
         None => Vec::new(),
     };
 
     let mut stream = res.bytes_stream();
     while let Some(item) = stream.next().await {
         let chunk = item.context("Stream error while downloading")?;
+        let before = body.len() as u64;
         body.extend_from_slice(&chunk);
-        pb.inc(chunk.len() as u64);
+        let downloaded = body.len() as u64;
+        if output::should_report(before, downloaded, total_size) {
+            output::emit(&Event::DownloadProgress { label, downloaded, total: total_size });
+        }
     }
 
     // Validate we got what we expected
-    if let Some(expected) = total_size {
-        if body.len() as u64 != expected {
-            return Err(anyhow!(
-                "Download incomplete: got {} bytes, expected {}",
-                body.len(),
-                expected
-            ));
-        }
+    if let Some(expected) = total_size
+        && body.len() as u64 != expected
+    {
+        return Err(Error::new(
+            ErrorKind::Integrity,
+            format!("Download incomplete: got {} bytes, expected {}", body.len(), expected),
+        ));
     }
 
-    pb.finish_and_clear();
+    if total_size.is_none() {
+        let downloaded = body.len() as u64;
+        output::emit(&Event::DownloadProgress { label, downloaded, total: Some(downloaded) });
+    }
     Ok(body)
 }
 
 
-fn extract_zip(bytes: Vec<u8>, dest: &PathBuf, label: &str) -> Result<()> {
-    paris::info!("Extracting {label} client...");
+fn extract_zip(bytes: Vec<u8>, dest: &Path, label: &str) -> Result<()> {
+    output::info!("Extracting {label} client...");
     fs::create_dir_all(dest)
-        .with_context(|| format!("Failed to create directory {dest:?}"))?;
+        .with_context(|| format!("Failed to create directory {}", dest.display()))?;
 
     let reader = Cursor::new(bytes);
     let mut zip = ZipArchive::new(reader).context("Failed to open zip archive")?;
 
-    let pb = ProgressBar::new(zip.len() as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{msg}\n{percent}% |{bar}| {pos}/{len} [{elapsed_precise}<{eta_precise}, {per_sec}]\n")?
-            .progress_chars("█▌ "),
-    );
-    pb.set_message("Extracting...");
+    let total = zip.len() as u64;
+    output::emit(&Event::ExtractionProgress { label, extracted: 0, total });
 
     for i in 0..zip.len() {
+        let extracted = i as u64;
+        if extracted > 0 && output::should_report(extracted - 1, extracted, Some(total)) {
+            output::emit(&Event::ExtractionProgress { label, extracted, total });
+        }
+        if cancel::is_cancelled() {
+            return Err(cancel::error());
+        }
         let Ok(mut file) = zip.by_index(i) else {
-            paris::warn!("Failed to extract file at index {i}, skipping");
-            pb.inc(1);
+            output::warn!("Failed to extract file at index {i}, skipping");
             continue;
         };
 
         let Some(rel_path) = file.enclosed_name() else {
-            paris::warn!("File at index {i} has an unsafe path, skipping");
-            pb.inc(1);
+            output::warn!("File at index {i} has an unsafe path, skipping");
             continue;
         };
 
@@ -179,74 +215,88 @@ fn extract_zip(bytes: Vec<u8>, dest: &PathBuf, label: &str) -> Result<()> {
 
         if file.is_dir() {
             if let Err(e) = fs::create_dir_all(&path) {
-                paris::error!("Failed to create dir {path:?}: {e}");
+                output::error!("Failed to create dir {path:?}: {e}");
             }
         } else {
-            if let Some(parent) = path.parent() {
-                if !parent.exists() {
-                    if let Err(e) = fs::create_dir_all(parent) {
-                        paris::error!("Failed to create parent dir {parent:?}: {e}");
-                        pb.inc(1);
-                        continue;
-                    }
-                }
+            if let Some(parent) = path.parent()
+                && !parent.exists()
+                && let Err(e) = fs::create_dir_all(parent)
+            {
+                output::error!("Failed to create parent dir {}: {e}", parent.display());
+                    continue;
             }
 
             match File::create(&path) {
                 Ok(mut fsfile) => {
                     if let Err(e) = io::copy(&mut file, &mut fsfile) {
-                        paris::error!("Failed to write {path:?}: {e}");
+                        output::error!("Failed to write {path:?}: {e}");
                     }
                 }
-                Err(e) => paris::error!("Failed to create {path:?}: {e}"),
+                Err(e) => output::error!("Failed to create {path:?}: {e}"),
             }
         }
-
-        pb.inc(1);
     }
 
-    pb.finish_with_message(format!("Extracted {label} client."));
+    output::emit(&Event::ExtractionProgress { label, extracted: total, total });
     Ok(())
 }
 
+/// The Wine prefix used for the clients, kept inside the install dir so
+/// it doesn't mix with the user's default one.
 #[cfg(target_os = "linux")]
-fn check_wine() -> Result<()> {
-    use std::path::Path;
+pub fn get_wine_prefix() -> Result<PathBuf> {
+    Ok(get_install_dir()?.join("prefix"))
+}
 
-    paris::log!("Checking for wine...");
-    let output = Command::new("wine")
+#[cfg(target_os = "linux")]
+fn check_wine(runner: &Runner, prefix: &Path) -> Result<()> {
+    output::log!("Checking for wine ({runner})...");
+    let output = runner
+        .command(prefix)
         .arg("--version")
         .output()
+        .with_kind(ErrorKind::Wine)
         .context("Failed to execute wine, is it installed?")?;
 
     if output.status.success() {
-        paris::info!(
-            "Wine detected: {}",
-            String::from_utf8_lossy(&output.stdout).trim()
-        );
+        let version = String::from_utf8_lossy(&output.stdout);
+        output::info!("Wine detected: {}", version.trim());
+        match wine::parse_version(&version) {
+            Some(found) if found < MIN_WINE_VERSION => {
+                return Err(Error::new(
+                    ErrorKind::Wine,
+                    format!(
+                        "Wine {}.{} is too old, {NAME} needs at least {}.{}",
+                        found.0, found.1, MIN_WINE_VERSION.0, MIN_WINE_VERSION.1
+                    ),
+                ));
+            }
+            Some(_) => {}
+            None => output::warn!("Could not read the Wine version, continuing anyway."),
+        }
     } else {
-        return Err(anyhow!(
-            "Wine check failed:\n{}",
-            String::from_utf8_lossy(&output.stderr)
+        return Err(Error::new(
+            ErrorKind::Wine,
+            format!("Wine check failed:\n{}", String::from_utf8_lossy(&output.stderr)),
         ));
     }
 
-    let home = env::var("HOME").context("HOME not set")?;
-    let dot_wine = Path::new(&home).join(".wine");
-
-    if dot_wine.is_dir() {
-        paris::info!("Detected .wine folder.");
+    if prefix.join("system.reg").is_file() {
+        output::info!("Detected Wine prefix.");
     } else {
-        paris::info!(".wine folder not found. Running wineboot...");
-        let output = Command::new("wineboot")
+        output::info!("Wine prefix not found. Running wineboot...");
+        let output = runner
+            .command(prefix)
+            .args(["wineboot", "--init"])
             .output()
+            .with_kind(ErrorKind::Wine)
             .context("Failed to run wineboot")?;
         if output.status.success() {
-            paris::info!("Wine prefix initialized.");
+            output::info!("Wine prefix initialized.");
         } else {
-            return Err(anyhow!(
-                "wineboot failed:\n{}",
-                String::from_utf8_lossy(&output.stderr)
+            return Err(Error::new(
+                ErrorKind::Wine,
+                format!("wineboot failed:\n{}", String::from_utf8_lossy(&output.stderr)),
             ));
         }
     }
@@ -254,17 +304,284 @@ fn check_wine() -> Result<()> {
     Ok(())
 }
 
-#[allow(clippy::too_many_lines, reason = "code is more readable as it is")]
-pub async fn bootstrap() -> Result<()> {
+/// Checks the runner and sets up the prefix with the prerequisites and DXVK.
+#[cfg(target_os = "linux")]
+pub async fn prepare_wine(runner: &Runner, prefix: &Path) -> Result<()> {
+    output::phase("prepare_wine", None);
+    check_wine(runner, prefix)?;
+    prerequisites::install(runner, prefix).await?;
+    let settings = Settings::load()?;
+    if settings.dxvk && !dxvk::is_installed(prefix) {
+        dxvk::install(runner, prefix, &settings.dxvk_archives).await?;
+    }
+    Ok(())
+}
+
+/// Cleans up after an install step cancelled with Ctrl-C, called while the lock is still held
+/// so no other bootstrapper writes to the install dir meanwhile.
+fn clean_if_cancelled<T>(result: Result<T>) -> Result<T> {
+    if !cancel::is_cancelled() {
+        return result;
+    }
+    output::log!("Cleaning up...");
+    if let Err(e) = clean_partial_install() {
+        output::error!("Failed to clean up: {e}");
+    }
+    // A Wine command killed by the same Ctrl-C fails with its own error.
+    Err(result.err().map_or_else(cancel::error, |err| err.with_kind(ErrorKind::Cancelled)))
+}
+
+/// Removes what an interrupted install leaves behind: staging folders of
+/// clients being extracted, and every client when no version was recorded yet.
+pub fn clean_partial_install() -> Result<()> {
+    let install_dir = get_install_dir()?;
+    let versions_dir = install_dir.join("Versions");
+
+    if !install_dir.join("version").is_file() {
+        if versions_dir.is_dir() {
+            fs::remove_dir_all(&versions_dir).context("Failed to remove partial clients")?;
+        }
+        let _ = fs::remove_file(install_dir.join("clients"));
+        return Ok(());
+    }
+
+    let Ok(versions) = fs::read_dir(&versions_dir) else {
+        return Ok(());
+    };
+    for version in versions.filter_map(Result::ok) {
+        let Ok(clients) = fs::read_dir(version.path()) else {
+            continue;
+        };
+        for client in clients.filter_map(Result::ok) {
+            if client.file_name().to_string_lossy().ends_with(PARTIAL_SUFFIX) {
+                fs::remove_dir_all(client.path())
+                    .with_context(|| format!("Failed to remove {}", client.path().display()))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Years recorded in the `clients` file of the install dir.
+/// Installs made before clients could be picked have all of them.
+pub fn installed_clients(install_dir: &Path) -> Vec<String> {
+    match fs::read_to_string(install_dir.join("clients")) {
+        Ok(list) => list
+            .lines()
+            .map(str::trim)
+            .filter(|year| !year.is_empty())
+            .map(String::from)
+            .collect(),
+        Err(_) => YEARS.iter().map(ToString::to_string).collect(),
+    }
+}
+
+fn write_installed_clients(install_dir: &Path, years: &[String]) -> Result<()> {
+    fs::write(install_dir.join("clients"), years.join("\n")).context("Failed to write clients file")
+}
+
+/// Checks the years given on the command line against the profile's, `none` meaning no client at all.
+/// Years given twice are only kept once.
+pub fn parse_selection(args: &[String], years: &[&str]) -> Result<Vec<String>> {
+    if args.iter().any(|arg| arg == "none") {
+        return Ok(Vec::new());
+    }
+    let mut selection: Vec<String> = Vec::new();
+    for year in args {
+        if !years.contains(&year.as_str()) {
+            return Err(Error::new(
+                ErrorKind::Usage,
+                format!("Unknown client {year}, available clients: {}", years.join(", ")),
+            ));
+        }
+        if !selection.contains(year) {
+            selection.push(year.clone());
+        }
+    }
+    Ok(selection)
+}
+
+async fn install_client(client: &Client, install_dir: &Path, version: &str, year: &str) -> Result<()> {
+    let url = format!("{SETUP}/{version}-{CLIENTFILENAMEPREFIX}{year}.zip");
+
+    output::phase("install_client", Some(year));
+    output::log!("Downloading {year} client...");
+    let bytes = download_with_retry(client, &url, year).await
+        .with_context(|| format!("Failed to download {year} client"))?;
+    output::success!("Downloaded {year} client.");
+
+    // Extract next to the client and swap it in once complete, so an interrupted
+    // extraction never looks like an installed client.
+    let client_path = install_dir.join("Versions").join(version).join(year);
+    let staging_path = install_dir.join("Versions").join(version).join(format!("{year}{PARTIAL_SUFFIX}"));
+    if staging_path.is_dir() {
+        fs::remove_dir_all(&staging_path)
+            .with_context(|| format!("Failed to remove {}", staging_path.display()))?;
+    }
+    extract_zip(bytes, &staging_path, year)
+        .with_context(|| format!("Failed to extract {year} client"))?;
+    if client_path.is_dir() {
+        fs::remove_dir_all(&client_path)
+            .with_context(|| format!("Failed to remove {}", client_path.display()))?;
+    }
+    fs::rename(&staging_path, &client_path)
+        .with_context(|| format!("Failed to move {year} client into place"))?;
+    mods::apply(&client_path, year)?;
+    output::success!("Installed {year} client.");
+    Ok(())
+}
+
+/// Installed clients of `version` whose folder or player executable is missing. Disabled
+/// clients are left out, they are only downloaded once enabled again.
+#[must_use]
+pub fn find_broken_clients(install_dir: &Path, version: &str, settings: &Settings) -> Vec<String> {
+    installed_clients(install_dir)
+        .into_iter()
+        .filter(|year| clients::is_enabled(settings, year))
+        .filter(|year| {
+            let client_path = install_dir.join("Versions").join(version).join(year);
+            let executable = launcher::find_launch_mode(year, "play").map(|mode| mode.executable);
+            !client_path.is_dir() || executable.is_ok_and(|executable| !client_path.join(executable).is_file())
+        })
+        .collect()
+}
+
+/// Downloads a client that a URI asked for if it was left out of the install or is broken.
+/// The caller holds the install lock.
+pub async fn ensure_client(version: &str, year: &str) -> Result<()> {
+    if !YEARS.contains(&year) {
+        return Err(Error::new(ErrorKind::Usage, format!("Unknown client {year}")));
+    }
+    let install_dir = get_install_dir()?;
+    let settings = Settings::load()?;
+    let mut installed = installed_clients(&install_dir);
+    let is_installed = installed.iter().any(|y| y == year);
+    if is_installed && !find_broken_clients(&install_dir, version, &settings).iter().any(|y| y == year) {
+        return Ok(());
+    }
+
+    let client = build_client()?;
+
+    if is_installed {
+        output::warn!("{year} client is broken, repairing it...");
+    } else {
+        output::info!("{year} client is not installed, installing it now...");
+    }
+    clean_if_cancelled(install_client(&client, &install_dir, version, year).await)?;
+
+    if !is_installed {
+        installed.push(year.to_string());
+    }
+    write_installed_clients(&install_dir, &installed)
+}
+
+/// Downloads the broken clients of the current version again, or all of them with `all`.
+pub async fn repair(all: bool) -> Result<()> {
+    let install_dir = get_install_dir()?;
+    let (up_to_date, version) = is_up_to_update().await?;
+    if !up_to_date {
+        return bootstrap(None).await;
+    }
+
+    let client = build_client()?;
+    let _lock = InstallLock::acquire().await?;
+    let settings = Settings::load()?;
+    let years = if all {
+        installed_clients(&install_dir).into_iter().filter(|year| clients::is_enabled(&settings, year)).collect()
+    } else {
+        find_broken_clients(&install_dir, &version, &settings)
+    };
+    if years.is_empty() {
+        output::success!("All {NAME} clients look fine, run `repair all` to reinstall them anyway.");
+        return Ok(());
+    }
+
+    output::info!("Repairing {NAME} {version} ({})...", years.join(", "));
+    for year in years {
+        clean_if_cancelled(install_client(&client, &install_dir, &version, &year).await)?;
+    }
+    output::success!("{NAME} repaired.");
+    Ok(())
+}
+
+/// Copies the running bootstrapper into the install dir and registers it as the URI handler.
+fn install_launcher() -> Result<()> {
+    output::log!("Copying self to install directory...");
+    let launcher_path = get_launcher_path()?;
+    let current_exe = current_exe()?;
+    // Copying the launcher onto itself (when it runs an update) would truncate it.
+    if fs::canonicalize(&current_exe).ok() != fs::canonicalize(&launcher_path).ok() {
+        fs::copy(&current_exe, &launcher_path).context("Failed to copy launcher")?;
+    }
+
+    output::phase("register", None);
+    output::log!("Setting up Launcher and uninstall shortcut...");
+    utils::register_uri(URI, &launcher_path)
+        .with_kind(ErrorKind::Platform)
+        .context("Failed to register URI handler")?;
+    utils::add_uninstall_shortcut(&launcher_path)
+        .with_kind(ErrorKind::Platform)
+        .context("Failed to add uninstall shortcut")?;
+    Ok(())
+}
+
+/// Installs or updates the clients. `selection` picks which years to add,
+/// `None` meaning all of them on a fresh install and the already installed ones on an update.
+pub async fn bootstrap(selection: Option<Vec<String>>) -> Result<()> {
     let client = build_client()?;
     let install_dir = get_install_dir()?;
+    let _lock = InstallLock::acquire().await?;
+    clean_if_cancelled(bootstrap_locked(&client, &install_dir, selection).await)
+}
 
-    let is_an_update: bool = if install_dir.is_dir(){
-        paris::log!("{NAME} already installed, Checking for updates...");
+async fn bootstrap_locked(client: &Client, install_dir: &Path, selection: Option<Vec<String>>) -> Result<()> {
+    output::phase("bootstrap", None);
+
+    // The install dir can exist without any client in it (settings, the lock file),
+    // only a version file means something was installed.
+    let is_an_update: bool = if install_dir.join("version").is_file() {
+        output::log!("{NAME} already installed, Checking for updates...");
         let (up_to_date, latest_version) = is_up_to_update().await?;
         if up_to_date {
-            paris::info!("Latest version of {NAME}, {latest_version} installed. Nothing to do.");
-            open::that(format!("https://www.{URL}/games"))?;
+            // Installs made before a prerequisite or the version check was added still get them.
+            #[cfg(target_os = "linux")]
+            prepare_wine(&Settings::load()?.runner, &get_wine_prefix()?).await?;
+
+            let settings = Settings::load()?;
+            let broken = find_broken_clients(install_dir, &latest_version, &settings);
+            if !broken.is_empty() {
+                output::warn!("Some clients are broken ({}), repairing them...", broken.join(", "));
+                for year in &broken {
+                    install_client(client, install_dir, &latest_version, year).await?;
+                }
+            }
+
+            let mut installed = installed_clients(install_dir);
+            let missing: Vec<String> = selection
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|year| !installed.contains(year))
+                .collect();
+            if missing.is_empty() {
+                if broken.is_empty() {
+                    output::info!("Latest version of {NAME}, {latest_version} installed. Nothing to do.");
+                } else {
+                    output::success!("{NAME} clients repaired.");
+                }
+                output::completed("bootstrap");
+                return Ok(());
+            }
+            for year in missing {
+                if clients::is_enabled(&settings, &year) {
+                    install_client(client, install_dir, &latest_version, &year).await?;
+                } else {
+                    output::warn!("The {year} client is disabled, it will be downloaded once enabled with `client {year} enable`.");
+                }
+                installed.push(year);
+            }
+            write_installed_clients(install_dir, &installed)?;
+            output::success!("Selected {NAME} clients installed.");
+            output::completed("bootstrap");
             return Ok(());
         }
         true
@@ -272,55 +589,155 @@ pub async fn bootstrap() -> Result<()> {
         false
     };
 
+    let latest_version = fetch_latest_version(client).await?;
+
+    let years: Vec<String> = match selection {
+        Some(mut years) if is_an_update => {
+            for year in installed_clients(install_dir) {
+                if !years.contains(&year) {
+                    years.push(year);
+                }
+            }
+            years
+        }
+        Some(years) => years,
+        None if is_an_update => installed_clients(install_dir),
+        None => YEARS.iter().map(ToString::to_string).collect(),
+    };
+    // Disabled clients stay recorded as installed so they come back once enabled, but aren't downloaded.
+    let settings = Settings::load()?;
+    let (enabled, disabled): (Vec<String>, Vec<String>) =
+        years.iter().cloned().partition(|year| clients::is_enabled(&settings, year));
+
+    fs::create_dir_all(install_dir)
+        .with_context(|| format!("Failed to create install dir {}", install_dir.display()))?;
+
     #[cfg(target_os = "linux")]
-    check_wine()?;
+    prepare_wine(&Settings::load()?.runner, &get_wine_prefix()?).await?;
 
-    let latest_version = fetch_latest_version(&client).await?;
+    if is_an_update {
+        output::info!("Updating {NAME} clients to {latest_version}...");
+    } else {
+        output::info!("Installing {NAME} {latest_version}...");
+    }
+
+    if !disabled.is_empty() {
+        output::info!("Skipping disabled clients ({}).", disabled.join(", "));
+    }
+    for year in &enabled {
+        install_client(client, install_dir, &latest_version, year).await?;
+    }
+    write_installed_clients(install_dir, &years)?;
+    fs::write(install_dir.join("version"), &latest_version).context("Failed to write version file")?;
 
-    fs::create_dir_all(&install_dir)
-        .with_context(|| format!("Failed to create install dir {install_dir:?}"))?;
-    env::set_current_dir(&install_dir)?;
+    install_launcher()?;
 
     if is_an_update {
-        paris::info!("Updating {NAME} clients to {latest_version}...");
+        output::success!("All {NAME} clients updated to {latest_version}.");
     } else {
-        paris::info!("Installing {NAME} {latest_version}...");
+        output::success!("All {NAME} clients installed. Have fun! :3");
     }
+    output::completed("bootstrap");
+    Ok(())
+}
 
-    for year in &YEARS {
-        let url = format!("{SETUP}/{latest_version}-{CLIENTFILENAMEPREFIX}{year}.zip");
+/// Installs, updates, repairs and uninstalls the clients of the [`Profile`] this build targets.
+/// Only one of them runs at a time on a machine, the others wait for it to finish.
+#[derive(Default)]
+pub struct Installer {
+    profile: Profile,
+    clients: Option<Vec<String>>,
+    progress: Option<Arc<dyn Progress>>,
+}
 
-        paris::log!("Downloading {year} client...");
-        let bytes = download_with_retry(&client, &url, &year.to_string()).await
-            .with_context(|| format!("Failed to download {year} client"))?;
-        paris::success!("Downloaded {year} client.");
+impl Installer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        let client_path = PathBuf::from(format!("Versions/{latest_version}/{year}"));
-        extract_zip(bytes, &client_path, &year.to_string())
-            .with_context(|| format!("Failed to extract {year} client"))?;
-        paris::success!("Installed {year} client.");
+    /// The revival this installer installs the clients of, see [`Profile`] for what it can't change.
+    #[must_use]
+    pub fn profile(&self) -> &Profile {
+        &self.profile
     }
-    fs::write("version", &latest_version).context("Failed to write version file")?;
 
-    paris::log!("Copying self to install directory...");
-    #[cfg(windows)]
-    let launcher_path = install_dir.join("launcher.exe");
-    #[cfg(target_os = "linux")]
-    let launcher_path = install_dir.join("launcher");
-    fs::copy(current_exe()?, &launcher_path).context("Failed to copy launcher")?;
+    /// Only installs these years (or none at all with `["none"]`) instead of every client,
+    /// no years at all installs every client. Updates keep the clients already installed.
+    #[must_use]
+    pub fn clients(mut self, years: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.clients = Some(years.into_iter().map(Into::into).collect());
+        self
+    }
 
-    
-    paris::log!("Setting up Launcher and uninstall shortcut...");
-    utils::register_uri(URI, &launcher_path)
-        .map_err(|e| anyhow!("Failed to register URI handler: {e}"))?;
-    utils::add_uninstall_shortcut(&launcher_path)
-        .map_err(|e| anyhow!("Failed to add uninstall shortcut: {e}"))?;
+    /// Receives the events of this installer's operations, instead of the default reporter
+    /// printing to the terminal.
+    #[must_use]
+    pub fn progress(mut self, progress: impl Progress + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
 
-    if is_an_update {
-        paris::success!("All {NAME} clients updated to {latest_version}.");
-    } else {
-        paris::success!("All {NAME} clients installed. Have fun! :3");
-        open::that(POST_INSTALL_URL)?;
+    /// Installs the clients, or updates them when a new version is out, and registers the URI handler.
+    pub async fn install(&self) -> Result<()> {
+        let selection = self
+            .clients
+            .as_deref()
+            .filter(|clients| !clients.is_empty())
+            .map(|clients| parse_selection(clients, self.profile.years))
+            .transpose()?;
+        output::scoped(self.progress.as_ref(), bootstrap(selection)).await
+    }
+
+    /// Downloads the broken clients again, or every installed client with `all`.
+    pub async fn repair(&self, all: bool) -> Result<()> {
+        output::scoped(self.progress.as_ref(), repair(all)).await
+    }
+
+    /// Whether the installed clients are the latest version, and the latest version.
+    pub async fn is_up_to_date(&self) -> Result<(bool, String)> {
+        is_up_to_update().await
+    }
+
+    /// Removes everything the bootstrapper installed, but the Wine prefix with `keep_prefix`.
+    pub async fn uninstall(&self, keep_prefix: bool) -> Result<()> {
+        output::scoped(self.progress.as_ref(), uninstall::remove(keep_prefix)).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_selection;
+    use crate::error::ErrorKind;
+
+    const YEARS: &[&str] = &["2016E", "2017L", "2020L"];
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn selected_clients_are_kept_in_order() {
+        assert_eq!(parse_selection(&args(&["2020L", "2016E"]), YEARS).unwrap(), args(&["2020L", "2016E"]));
+        assert!(parse_selection(&[], YEARS).unwrap().is_empty());
+    }
+
+    #[test]
+    fn duplicates_are_kept_once() {
+        assert_eq!(parse_selection(&args(&["2017L", "2017L"]), YEARS).unwrap(), args(&["2017L"]));
+        assert_eq!(parse_selection(&args(&["2020L", "2017L", "2020L"]), YEARS).unwrap(), args(&["2020L", "2017L"]));
+    }
+
+    #[test]
+    fn none_selects_nothing() {
+        assert!(parse_selection(&args(&["none"]), YEARS).unwrap().is_empty());
+        assert!(parse_selection(&args(&["2017L", "none"]), YEARS).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unknown_clients_are_usage_errors() {
+        let err = parse_selection(&args(&["2017L", "2008M"]), YEARS).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Usage);
+        assert!(err.to_string().contains("2008M"));
     }
-    Ok(())
 }
@@ -0,0 +1,59 @@
+use std::{
+    process,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use tokio::sync::Notify;
+
+use crate::{
+    error::{Error, ErrorKind},
+    lock, output,
+};
+
+/// Exit code used when the user interrupts the bootstrapper.
+pub const EXIT_CODE: i32 = 130;
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+static NOTIFY: Notify = Notify::const_new();
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Resolves once Ctrl-C was pressed, to stop downloads and waits with `tokio::select!`.
+pub async fn cancelled() {
+    // Created before checking the flag so a Ctrl-C in between isn't missed.
+    let notified = NOTIFY.notified();
+    if is_cancelled() {
+        return;
+    }
+    notified.await;
+}
+
+/// The error install steps return once cancelled.
+#[must_use]
+pub fn error() -> Error {
+    Error::new(ErrorKind::Cancelled, "Cancelled")
+}
+
+/// Handles Ctrl-C. Without an install running the bootstrapper exits with `EXIT_CODE` right away,
+/// otherwise the install stops at its next step and cleans up while it still holds the lock.
+/// A second Ctrl-C exits without waiting.
+pub fn handle_ctrl_c() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        CANCELLED.store(true, Ordering::SeqCst);
+        NOTIFY.notify_waiters();
+
+        if !lock::is_held() {
+            output::warn!("Cancelled.");
+            process::exit(EXIT_CODE);
+        }
+        output::warn!("Cancelling, press Ctrl-C again to exit right away...");
+        if tokio::signal::ctrl_c().await.is_ok() {
+            process::exit(EXIT_CODE);
+        }
+    });
+}
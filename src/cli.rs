@@ -0,0 +1,193 @@
+//! The `korone-bootstrapper` command line.
+use std::{
+    env,
+    io::{self, IsTerminal, Write},
+    process,
+    sync::Arc,
+};
+
+use figlet_rs::FIGfont;
+use crate::{
+    bootstrapper, cancel, clients, config, doctor, flags, launcher::AfterLaunch, logs, mods, output, progress::{JsonReporter, SilentReporter}, self_update,
+    settings::{self, Settings}, status, uninstall, Error, ErrorKind, Installer, Launcher, Result,
+};
+#[cfg(target_os = "linux")]
+use crate::{dxvk, wine};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+fn print_banner() {
+    let font = FIGfont::from_content(config::FIGLET_FONT).unwrap();
+    let figlet_text = font.convert(config::NAME).unwrap().to_string();
+
+    let mut stdout = StandardStream::stdout(ColorChoice::Auto);
+    stdout
+        .set_color(ColorSpec::new().set_fg(Some(Color::White)))
+        .unwrap();
+    write!(&mut stdout, "{figlet_text}").unwrap();
+    stdout.reset().unwrap();
+    println!();
+    stdout
+        .set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))
+        .unwrap();
+    write!(&mut stdout, "URL: https://www.{}/", config::URL).unwrap();
+    stdout.reset().unwrap();
+    println!();
+}
+
+/// Only when someone is there to press it.
+fn wait_for_enter(json: bool) {
+    if !json && io::stdin().is_terminal() {
+        print!("Press Enter to close...");
+        let _ = io::stdout().flush();
+        let _ = io::stdin().read_line(&mut String::new());
+    }
+}
+
+/// Installs or updates the clients, then opens the thank-you page after a first install,
+/// or the games page when there was nothing to update.
+async fn install(installer: Installer) -> Result<()> {
+    let before = bootstrapper::installed_version();
+    installer.install().await?;
+    let page = if before.is_none() {
+        config::POST_INSTALL_URL.to_string()
+    } else if bootstrapper::installed_version() == before {
+        format!("https://www.{}/games", config::URL)
+    } else {
+        return Ok(());
+    };
+    if let Err(e) = open::that(&page) {
+        output::warn!("Failed to open {page}: {e}");
+    }
+    Ok(())
+}
+
+/// Runs the command given in the process arguments, and exits with the error's exit code when it fails.
+#[allow(clippy::too_many_lines, reason = "code is more readable as it is")]
+pub async fn run() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    // `status --json` prints its own JSON, nothing else may land on stdout.
+    let quiet = args.iter().any(|arg| arg == "--json");
+    if quiet {
+        output::set_progress(Arc::new(SilentReporter));
+    }
+    let mut json = quiet;
+    if let Some(i) = args.iter().position(|arg| arg == "--output") {
+        let format = args.drain(i..(i + 2).min(args.len())).nth(1);
+        match format.as_deref() {
+            Some("json") => {
+                output::set_progress(Arc::new(JsonReporter));
+                json = true;
+            }
+            Some("text") => {}
+            _ => {
+                let err = Error::new(ErrorKind::Usage, "Usage: --output <text|json>");
+                err.report("Error");
+                process::exit(err.exit_code());
+            }
+        }
+    }
+
+    // Keep stdout parseable for scripts.
+    if !json {
+        print_banner();
+    }
+    cancel::handle_ctrl_c();
+
+    let launched = args.first().is_some_and(|command| command.starts_with(config::URI));
+    // Only commands that install or start clients update the bootstrapper and keep a run log, the others
+    // just read or change settings and must not create the install dir on a machine without an install.
+    let installs = launched || matches!(args.first().map(String::as_str), None | Some("install" | "repair"));
+    let run_log = if installs {
+        logs::start_run_log().inspect_err(|err| output::warn!("Could not create the run log: {err}")).ok()
+    } else {
+        None
+    };
+    output::debug!("{} bootstrapper {} on {}, arguments: {args:?}", config::NAME, env!("CARGO_PKG_VERSION"), env::consts::OS);
+    if installs {
+        self_update::check().await;
+    }
+
+    let mut args = args.into_iter();
+    let command = args.next();
+    let (doing, result) = match command {
+        None => ("Error while bootstrapping", install(Installer::new()).await),
+        Some(x) if x == "install" => ("Error while bootstrapping", install(Installer::new().clients(args)).await),
+        Some(x) if x == "repair" => ("Error while repairing", Installer::new().repair(args.next().as_deref() == Some("all")).await),
+        Some(x) if x.starts_with(config::URI) => ("Error while launching", Launcher::new().launch(&x).await),
+        #[cfg(target_os = "linux")]
+        Some(x) if x == "runners" => (
+            "Error while managing runners",
+            match (args.next().as_deref(), args.next()) {
+                (Some("list") | None, _) => wine::list_runners(),
+                (Some("set"), Some(choice)) => wine::set_runner(&choice),
+                _ => Err(Error::new(ErrorKind::Usage, "Usage: runners list | runners set <system|number|path>")),
+            },
+        ),
+        #[cfg(target_os = "linux")]
+        Some(x) if x == "dxvk" => (
+            "Error while managing DXVK",
+            match args.next().as_deref() {
+                Some("enable") => dxvk::enable(args.map(Into::into).collect()).await,
+                Some("disable") => dxvk::disable(),
+                _ => Err(Error::new(ErrorKind::Usage, "Usage: dxvk enable [archives...] | dxvk disable")),
+            },
+        ),
+        Some(x) if x == "logs" => ("Error while reading logs", logs::main(args.next().as_deref() == Some("open"))),
+        Some(x) if x == "flags" => (
+            "Error while changing client flags",
+            match (args.next().as_deref(), args.next(), args.next(), args.next()) {
+                (Some("set"), Some(year), Some(name), Some(value)) => flags::set(&year, &name, &value),
+                (Some("unset"), Some(year), Some(name), _) => flags::unset(&year, &name),
+                (Some("set" | "unset"), ..) => Err(Error::new(ErrorKind::Usage, "Usage: flags [year] | flags set <year|all> <flag> <value> | flags unset <year|all> <flag>")),
+                (year, ..) => flags::list(year),
+            },
+        ),
+        Some(x) if x == "mods" => (
+            "Error while managing modifications",
+            match args.next().as_deref() {
+                None => mods::show(),
+                Some("apply") => mods::set_enabled(true),
+                Some("restore") => mods::set_enabled(false),
+                _ => Err(Error::new(ErrorKind::Usage, "Usage: mods | mods apply | mods restore")),
+            },
+        ),
+        Some(x) if x == "client" => (
+            "Error while changing client settings",
+            match (args.next(), args.next()) {
+                (Some(year), action) => clients::main(&year, action.as_deref(), &args.collect::<Vec<_>>()),
+                (None, _) => Err(Error::new(ErrorKind::Usage, "Usage: client <year> [action]")),
+            },
+        ),
+        Some(x) if x == "status" => ("Error while reading the status", status::main(args.next().as_deref() == Some("--json")).await),
+        Some(x) if x == "doctor" => ("Error while running the checks", doctor::main().await),
+        Some(x) if x == "settings" => (
+            "Error while changing settings",
+            match (args.next().as_deref(), args.next(), args.next()) {
+                (None, _, _) => settings::show(),
+                (Some("set"), Some(key), Some(value)) => settings::set(&key, &value),
+                _ => Err(Error::new(ErrorKind::Usage, "Usage: settings | settings set <key> <value>")),
+            },
+        ),
+        Some(x) if x.starts_with("uninstall") => ("Error while uninstalling", uninstall::main(json).await),
+        Some(x) => ("Error", Err(Error::new(ErrorKind::Usage, format!("Unknown argument: {x}")))),
+    };
+    if let Err(err) = &result {
+        if quiet {
+            eprintln!("{doing}: {err:#}");
+        } else {
+            err.report(doing);
+        }
+        if let Some(path) = &run_log {
+            output::info!("The log of this run is at {}", path.display());
+        }
+        // Keeps a window opened from a link up, so the error can be read.
+        if err.kind() != ErrorKind::Cancelled {
+            wait_for_enter(json);
+        }
+    } else if launched && Settings::load().is_ok_and(|settings| settings.after_launch == AfterLaunch::Wait) {
+        wait_for_enter(json);
+    }
+    if let Err(err) = result {
+        process::exit(err.exit_code());
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+
+use crate::error::{Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{CLIENT_DEFAULTS, YEARS},
+    output,
+    settings::Settings,
+};
+#[cfg(target_os = "linux")]
+use crate::{
+    bootstrapper::{get_install_dir, get_wine_prefix},
+    config::CLIENT_ENV,
+    wine::{self, Runner},
+};
+
+/// User overrides for one client, stored by year in `settings.json`.
+/// Unset fields fall back to the profile's `CLIENT_DEFAULTS` and the global settings.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    pub enabled: Option<bool>,
+    /// Appended to the launch mode's arguments.
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    #[cfg(target_os = "linux")]
+    pub runner: Option<Runner>,
+    /// Relative paths are inside the install dir.
+    #[cfg(target_os = "linux")]
+    pub prefix: Option<PathBuf>,
+}
+
+/// What a client is launched with once the profile and user settings are merged.
+pub struct ResolvedClient {
+    pub enabled: bool,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    #[cfg(target_os = "linux")]
+    pub runner: Runner,
+    #[cfg(target_os = "linux")]
+    pub prefix: PathBuf,
+}
+
+pub fn resolve(settings: &Settings, year: &str) -> Result<ResolvedClient> {
+    let user = settings.clients.get(year).cloned().unwrap_or_default();
+    let profile = CLIENT_DEFAULTS.iter().find(|defaults| defaults.year == year);
+
+    let mut args: Vec<String> = profile
+        .map(|defaults| defaults.args.iter().map(ToString::to_string).collect())
+        .unwrap_or_default();
+    args.extend(user.args);
+
+    let mut env: BTreeMap<String, String> = BTreeMap::new();
+    #[cfg(target_os = "linux")]
+    env.extend(CLIENT_ENV.iter().map(|(key, value)| (key.to_string(), value.to_string())));
+    if let Some(defaults) = profile {
+        env.extend(defaults.env.iter().map(|(key, value)| (key.to_string(), value.to_string())));
+    }
+    env.extend(user.env);
+
+    Ok(ResolvedClient {
+        enabled: user.enabled.unwrap_or(profile.is_none_or(|defaults| defaults.enabled)),
+        args,
+        env,
+        #[cfg(target_os = "linux")]
+        runner: user.runner.unwrap_or_else(|| settings.runner.clone()),
+        #[cfg(target_os = "linux")]
+        prefix: match user.prefix {
+            Some(prefix) => get_install_dir()?.join(prefix),
+            None => get_wine_prefix()?,
+        },
+    })
+}
+
+#[must_use]
+pub fn is_enabled(settings: &Settings, year: &str) -> bool {
+    resolve(settings, year).is_ok_and(|client| client.enabled)
+}
+
+/// `client <year>`
+fn show(settings: &Settings, year: &str) -> Result<()> {
+    let client = resolve(settings, year)?;
+    output::info!("{year} client ({})", if client.enabled { "enabled" } else { "disabled" });
+    println!("  args: {}", client.args.join(" "));
+    for (key, value) in &client.env {
+        println!("  env: {key}={value}");
+    }
+    #[cfg(target_os = "linux")]
+    {
+        println!("  runner: {}", client.runner);
+        println!("  prefix: {}", client.prefix.display());
+    }
+    Ok(())
+}
+
+/// `client <year> [enable | disable | args <args...> | env <KEY=VALUE> | runner <choice> | prefix <path>]`,
+/// `default` resets the runner or prefix to the global one.
+pub fn main(year: &str, action: Option<&str>, values: &[String]) -> Result<()> {
+    if !YEARS.contains(&year) {
+        return Err(Error::new(ErrorKind::Usage, format!("Unknown client {year}, available clients: {}", YEARS.join(", "))));
+    }
+    let mut settings = Settings::load()?;
+    let Some(action) = action else {
+        return show(&settings, year);
+    };
+
+    let client = settings.clients.entry(year.to_string()).or_default();
+    match (action, values) {
+        ("enable", _) => client.enabled = Some(true),
+        ("disable", _) => client.enabled = Some(false),
+        ("args", args) => client.args = args.to_vec(),
+        ("env", [pair]) => {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| Error::new(ErrorKind::Usage, format!("Expected KEY=VALUE, got {pair}")))?;
+            if value.is_empty() {
+                client.env.remove(key);
+            } else {
+                client.env.insert(key.to_string(), value.to_string());
+            }
+        }
+        #[cfg(target_os = "linux")]
+        ("runner", [choice]) => {
+            client.runner = match choice.as_str() {
+                "default" => None,
+                choice => Some(wine::parse_runner_choice(choice)?),
+            };
+        }
+        #[cfg(target_os = "linux")]
+        ("prefix", [path]) => {
+            client.prefix = (path != "default").then(|| PathBuf::from(path));
+        }
+        _ => {
+            return Err(Error::new(ErrorKind::Usage, "Usage: client <year> [enable | disable | args <args...> | env <KEY=VALUE> | runner <system|number|path|default> | prefix <path|default>]"));
+        }
+    }
+    settings.save()?;
+    show(&settings, year)
+}
@@ -9,9 +9,135 @@ pub const FIGLET_FONT: &str = include_str!("../assets/standard.flf");
 pub const CLIENTFILENAMEPREFIX: &str = "ProjectXApp"; // optional
 // The client filename should be (version)-(CLIENTFILENAMEPREFIX)(year).zip
 
+pub const AUTH_URL: &str = "https://www.{url}/Login/Negotiate.ashx"; // {url} is URL
+
+// Bootstrapper releases, SELF_UPDATE_URL/<windows|linux>.json should return
+// {"version": "0.1.2", "url": "...", "sha256": "<hex>", "signature": "<hex ed25519 signature of the binary>"}
+// Self-update is off while either is empty.
+pub const SELF_UPDATE_URL: &str = "https://setup.pekora.zip/bootstrapper";
+pub const SELF_UPDATE_PUBLIC_KEY: &str = ""; // hex encoded ed25519 public key
+
+pub const MAX_LOGS: usize = 10; // kept in the Logs folder, per kind of log
+
+/// How a client gets started for a given `launchmode` of the URI.
+/// `executable` is relative to the client folder and may point into a subfolder.
+#[derive(Debug)]
+pub struct LaunchMode {
+    pub name: &'static str,
+    pub executable: &'static str,
+    pub args: &'static [&'static str],
+}
+
+// Placeholders in args: {auth_url}, {game_info}, {place_launcher_url}, {year}, {version}, {url}
+// Change the studio arguments to whatever your revival's studio expects.
+pub const LAUNCH_MODES: [LaunchMode; 3] = [
+    LaunchMode {
+        name: "play",
+        executable: "ProjectXPlayerBeta.exe",
+        args: &["--play", "-a", "{auth_url}", "-t", "{game_info}", "-j", "{place_launcher_url}"],
+    },
+    LaunchMode {
+        name: "edit",
+        executable: "ProjectXStudioBeta.exe",
+        args: &["-task", "EditPlace", "-a", "{auth_url}", "-t", "{game_info}", "-j", "{place_launcher_url}"],
+    },
+    LaunchMode {
+        name: "build",
+        executable: "ProjectXStudioBeta.exe",
+        args: &["-task", "StartServer", "-a", "{auth_url}", "-t", "{game_info}", "-j", "{place_launcher_url}"],
+    },
+];
+
+// Per year replacements of LAUNCH_MODES, for clients that use other names or flags.
+// e.g. ("2017L", LaunchMode { name: "play", executable: "Player/ProjectXPlayer.exe", args: &["-play", "{place_launcher_url}"] })
+pub const CLIENT_OVERRIDES: &[(&str, LaunchMode)] = &[];
+
+/// Per year launch defaults, users can override them with the `client` command.
+pub struct ClientDefaults {
+    pub year: &'static str,
+    pub enabled: bool,
+    pub args: &'static [&'static str], // appended to the launch mode's arguments
+    pub env: &'static [(&'static str, &'static str)],
+}
+
+// e.g. ClientDefaults { year: "2021M", enabled: true, args: &[], env: &[("DXVK_ASYNC", "1")] }
+pub const CLIENT_DEFAULTS: &[ClientDefaults] = &[];
+
+/// The revival this build targets, gathered from the consts above for library users to read.
+/// It is informational only: the bootstrapper reads the consts, so a changed `Profile` doesn't
+/// change what gets installed or launched. Change the consts to target another revival.
+#[derive(Debug, Clone, Copy)]
+pub struct Profile {
+    pub name: &'static str,
+    /// Domain of the website, without `www.`.
+    pub url: &'static str,
+    /// Scheme of the URIs the website starts clients with.
+    pub uri: &'static str,
+    /// Where the `version` endpoint and the client archives are.
+    pub setup: &'static str,
+    pub years: &'static [&'static str],
+    pub launch_modes: &'static [LaunchMode],
+}
+
+impl Profile {
+    #[must_use]
+    pub const fn current() -> Self {
+        Self {
+            name: NAME,
+            url: URL,
+            uri: URI,
+            setup: SETUP,
+            years: &YEARS,
+            launch_modes: &LAUNCH_MODES,
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
 // Uninstall shorcut only (windows)
 #[cfg(windows)]
 pub const AUTHOR: &str = "larped";
 
 #[cfg(target_os = "linux")]
 pub const DESKTOP_ENTRY_VERSION: &str = "0.1"; // for linux distros
+
+#[cfg(target_os = "linux")]
+pub const MIN_WINE_VERSION: (u32, u32) = (8, 0); // (major, minor)
+
+// Environment variables set for the client, e.g. ("WINEDLLOVERRIDES", "d3d9=n,b"),
+// ("WINEESYNC", "1"), ("WINEFSYNC", "1") or ("DXVK_HUD", "fps")
+#[cfg(target_os = "linux")]
+pub const CLIENT_ENV: &[(&str, &str)] = &[("WINEDEBUG", "-all")];
+
+// DXVK / VKD3D-Proton archives (.tar.gz, .tar.zst or .zip) installed into the prefix by `dxvk enable`
+#[cfg(target_os = "linux")]
+pub const DXVK_URLS: &[&str] = &["https://github.com/doitsujin/dxvk/releases/download/v2.5.3/dxvk-2.5.3.tar.gz"];
+
+/// A dependency installed once into the Wine prefix. It goes through winetricks
+/// when it is installed, else `redistributable` is downloaded and run with `args`.
+#[cfg(target_os = "linux")]
+pub struct Prerequisite {
+    pub name: &'static str,
+    pub redistributable: Option<&'static str>,
+    pub args: &'static [&'static str],
+}
+
+// Names are winetricks verbs.
+#[cfg(target_os = "linux")]
+pub const WINE_PREREQUISITES: &[Prerequisite] = &[
+    Prerequisite {
+        name: "vcrun2019",
+        redistributable: Some("https://aka.ms/vs/16/release/vc_redist.x86.exe"),
+        args: &["/install", "/quiet", "/norestart"],
+    },
+    Prerequisite {
+        name: "d3dx9",
+        redistributable: None,
+        args: &[],
+    },
+];
@@ -0,0 +1,207 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::{Error, ErrorKind, Result};
+
+use crate::{
+    bootstrapper::{build_client, fetch_latest_version, get_install_dir, get_launcher_path},
+    config::{NAME, SETUP, URI},
+    output,
+    utils,
+};
+#[cfg(target_os = "linux")]
+use crate::{
+    bootstrapper::get_wine_prefix,
+    config::{MIN_WINE_VERSION, WINE_PREREQUISITES},
+    settings::Settings,
+    wine::{self, find_in_path},
+};
+
+/// Below this, installing or updating every client may run out of space.
+const MIN_FREE_SPACE: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Counts the problems found and prints each check with what to do about it.
+#[derive(Default)]
+struct Report {
+    passed: usize,
+    warnings: usize,
+    problems: usize,
+}
+
+impl Report {
+    fn ok(&mut self, check: &str, detail: &str) {
+        self.passed += 1;
+        output::success!("{check}: {detail}");
+    }
+
+    fn problem(&mut self, check: &str, detail: &str, fix: &str) {
+        self.problems += 1;
+        output::error!("{check}: {detail}");
+        output::info!("  Fix: {fix}");
+    }
+
+    fn warning(&mut self, check: &str, detail: &str, fix: &str) {
+        self.warnings += 1;
+        output::warn!("{check}: {detail}");
+        output::info!("  Fix: {fix}");
+    }
+}
+
+/// The install dir, or its closest existing parent before the first install.
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|dir| dir.is_dir()).map(Path::to_path_buf)
+}
+
+fn check_disk(report: &mut Report, install_dir: &Path) {
+    let Some(dir) = existing_ancestor(install_dir) else {
+        report.problem("Install dir", &format!("no parent of {} exists", install_dir.display()), "check that your home folder is set up");
+        return;
+    };
+
+    let probe = dir.join(".openstrap-write-test");
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            report.ok("Install dir", &format!("{} is writable", dir.display()));
+        }
+        Err(e) => report.problem(
+            "Install dir",
+            &format!("can't write to {} ({e})", dir.display()),
+            &format!("make sure your user owns {}", dir.display()),
+        ),
+    }
+
+    match fs4::available_space(&dir) {
+        Ok(free) if free < MIN_FREE_SPACE => report.problem(
+            "Disk space",
+            &format!("only {} MiB free", free / 1024 / 1024),
+            &format!("free at least {} MiB on the drive holding {}", MIN_FREE_SPACE / 1024 / 1024, dir.display()),
+        ),
+        Ok(free) => report.ok("Disk space", &format!("{} MiB free", free / 1024 / 1024)),
+        Err(e) => report.warning("Disk space", &format!("could not be read ({e})"), "check the free space by hand"),
+    }
+}
+
+async fn check_server(report: &mut Report) {
+    let result = match build_client() {
+        Ok(client) => fetch_latest_version(&client).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(version) => report.ok("Server", &format!("{SETUP}/version returned {version}")),
+        Err(e) => report.problem(
+            "Server",
+            &format!("{SETUP}/version is unreachable ({e:#})"),
+            "check your internet connection, firewall or proxy, or whether the revival is down",
+        ),
+    }
+}
+
+fn check_uri_handler(report: &mut Report) -> Result<()> {
+    let launcher_path = get_launcher_path()?;
+    match utils::get_uri_handler(URI) {
+        None => report.problem(
+            "URI handler",
+            &format!("nothing handles {URI} links"),
+            &format!("run the {NAME} bootstrapper without arguments to install it"),
+        ),
+        Some(command) if !command.contains(&*launcher_path.to_string_lossy()) => report.problem(
+            "URI handler",
+            &format!("{URI} links open {command}"),
+            &format!("run the {NAME} bootstrapper without arguments to register {} again", launcher_path.display()),
+        ),
+        Some(_) if !launcher_path.is_file() => report.problem(
+            "URI handler",
+            &format!("{} is missing", launcher_path.display()),
+            &format!("run the {NAME} bootstrapper without arguments to reinstall it"),
+        ),
+        Some(command) => report.ok("URI handler", &command),
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn check_tools(report: &mut Report) {
+    let tools = [
+        ("update-desktop-database", "install desktop-file-utils with your package manager"),
+        ("xdg-settings", "install xdg-utils with your package manager"),
+        ("xdg-mime", "install xdg-utils with your package manager"),
+    ];
+    for (tool, fix) in tools {
+        match find_in_path(tool) {
+            Some(path) => report.ok(tool, &path.display().to_string()),
+            None => report.problem(tool, "not found", fix),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn check_wine(report: &mut Report) -> Result<()> {
+    let runner = Settings::load()?.runner;
+    let prefix = get_wine_prefix()?;
+
+    if !runner.is_available() {
+        report.problem(
+            "Wine",
+            &format!("{runner} not found"),
+            "install Wine with your package manager, or pick another runner with `runners list` and `runners set`",
+        );
+        return Ok(());
+    }
+    let version = runner
+        .command(&prefix)
+        .arg("--version")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    match version.as_deref().map(wine::parse_version) {
+        Ok(Some(found)) if found < MIN_WINE_VERSION => report.problem(
+            "Wine",
+            &format!("{runner} is {}.{}, older than {}.{}", found.0, found.1, MIN_WINE_VERSION.0, MIN_WINE_VERSION.1),
+            "update Wine (e.g. from WineHQ's repositories) or pick a newer runner with `runners set`",
+        ),
+        Ok(Some(_)) => report.ok("Wine", &format!("{runner}, {}", version.unwrap_or_default())),
+        Ok(None) => report.warning("Wine", &format!("could not read the version of {runner}"), "make sure `wine --version` works"),
+        Err(e) => report.problem("Wine", &format!("{runner} failed to run ({e})"), "reinstall Wine or pick another runner with `runners set`"),
+    }
+
+    if prefix.join("system.reg").is_file() {
+        report.ok("Wine prefix", &prefix.display().to_string());
+    } else {
+        report.warning("Wine prefix", "not created yet", &format!("run the {NAME} bootstrapper without arguments to create it"));
+    }
+
+    let needs_winetricks = WINE_PREREQUISITES.iter().any(|prerequisite| prerequisite.redistributable.is_none());
+    match find_in_path("winetricks") {
+        Some(path) => report.ok("winetricks", &path.display().to_string()),
+        None if needs_winetricks => report.warning(
+            "winetricks",
+            "not found, the prerequisites that can only be installed with it are skipped",
+            "install winetricks with your package manager",
+        ),
+        None => report.ok("winetricks", "not found, not needed"),
+    }
+    Ok(())
+}
+
+/// `doctor`
+pub async fn main() -> Result<()> {
+    let mut report = Report::default();
+
+    check_disk(&mut report, &get_install_dir()?);
+    check_server(&mut report).await;
+    check_uri_handler(&mut report)?;
+    #[cfg(target_os = "linux")]
+    {
+        check_tools(&mut report);
+        check_wine(&mut report)?;
+    }
+
+    let summary = format!("{} passed, {} warning(s), {} problem(s)", report.passed, report.warnings, report.problems);
+    if report.problems > 0 {
+        return Err(Error::new(ErrorKind::Doctor, format!("{summary}, see the fixes above.")));
+    }
+    output::success!("{summary}");
+    Ok(())
+}
@@ -0,0 +1,303 @@
+use std::{
+    fs,
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::error::{Context, Error, ErrorKind, Result};
+use flate2::read::GzDecoder;
+use zip::ZipArchive;
+
+use crate::{
+    bootstrapper::{build_client, download_with_retry, get_wine_prefix},
+    clients,
+    config::{DXVK_URLS, YEARS},
+    output,
+    settings::Settings,
+    wine::Runner,
+};
+
+/// Lists the DLLs copied into the prefix, one `<system dir>/<name>.dll` per line.
+const STATE_FILE: &str = ".openstrap-dxvk";
+/// Added to the builtin DLLs replaced by DXVK's, to put them back on uninstall.
+const BUILTIN_SUFFIX: &str = ".openstrap-builtin";
+const DLL_OVERRIDES_KEY: &str = r"HKEY_CURRENT_USER\Software\Wine\DllOverrides";
+
+/// A DLL taken out of an archive, with the prefix folder it goes into.
+struct Dll {
+    name: String,
+    system_dir: &'static str,
+    bytes: Vec<u8>,
+}
+
+#[must_use]
+pub fn is_installed(prefix: &Path) -> bool {
+    prefix.join(STATE_FILE).is_file()
+}
+
+/// Maps the `x64` / `x32` / `x86` folders of DXVK and VKD3D-Proton archives
+/// to the folders of a 64 bit prefix.
+fn system_dir(path: &Path) -> Option<&'static str> {
+    if path.extension()? != "dll" {
+        return None;
+    }
+    match path.parent()?.file_name()?.to_str()? {
+        "x64" => Some("system32"),
+        "x32" | "x86" => Some("syswow64"),
+        _ => None,
+    }
+}
+
+fn read_tar(reader: impl Read) -> Result<Vec<Dll>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut dlls = Vec::new();
+    for entry in archive.entries().context("Failed to read tar archive")? {
+        let mut entry = entry.context("Failed to read tar entry")?;
+        let path = entry.path()?.into_owned();
+        let Some(system_dir) = system_dir(&path) else {
+            continue;
+        };
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        dlls.push(Dll {
+            name: path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+            system_dir,
+            bytes,
+        });
+    }
+    Ok(dlls)
+}
+
+fn read_zip(bytes: Vec<u8>) -> Result<Vec<Dll>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).context("Failed to open zip archive")?;
+    let mut dlls = Vec::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let Some(path) = file.enclosed_name() else {
+            continue;
+        };
+        let Some(system_dir) = system_dir(&path) else {
+            continue;
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        dlls.push(Dll {
+            name: path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+            system_dir,
+            bytes,
+        });
+    }
+    Ok(dlls)
+}
+
+fn unpack(bytes: Vec<u8>, name: &str) -> Result<Vec<Dll>> {
+    let lowercase_name = name.to_lowercase();
+    let name_ends_with = |suffix: &str| lowercase_name.ends_with(suffix);
+    let dlls = if name_ends_with(".zip") {
+        read_zip(bytes)?
+    } else if name_ends_with(".tar.zst") {
+        read_tar(zstd::Decoder::new(Cursor::new(bytes))?)?
+    } else if name_ends_with(".tar.gz") || name_ends_with(".tgz") {
+        read_tar(GzDecoder::new(Cursor::new(bytes)))?
+    } else {
+        return Err(Error::new(ErrorKind::Usage, format!("Unsupported archive {name}, expected .tar.gz, .tar.zst or .zip")));
+    };
+
+    if dlls.is_empty() {
+        return Err(Error::new(ErrorKind::Extraction, format!("No DLLs found in {name}")));
+    }
+    Ok(dlls)
+}
+
+fn run_reg(runner: &Runner, prefix: &Path, args: &[&str]) -> Result<()> {
+    let output = runner
+        .command(prefix)
+        .arg("reg")
+        .args(args)
+        .output()
+        .with_kind(ErrorKind::Wine)
+        .context("Failed to run wine reg")?;
+    if !output.status.success() {
+        return Err(Error::new(ErrorKind::Wine, format!("wine reg failed:\n{}", String::from_utf8_lossy(&output.stderr))));
+    }
+    Ok(())
+}
+
+/// Installs the DLLs of the given archives (or of the profile's `DXVK_URLS`)
+/// into the prefix and makes Wine prefer them over its builtin ones.
+pub async fn install(runner: &Runner, prefix: &Path, archives: &[PathBuf]) -> Result<()> {
+    let mut sources: Vec<(String, Vec<u8>)> = Vec::new();
+    if archives.is_empty() {
+        let client = build_client()?;
+        for url in DXVK_URLS {
+            let name = url.rsplit('/').next().unwrap_or(url).to_string();
+            let bytes = download_with_retry(&client, url, &name).await?;
+            sources.push((name, bytes));
+        }
+    } else {
+        for archive in archives {
+            let bytes = fs::read(archive)
+                .with_context(|| format!("Failed to read {}", archive.display()))?;
+            sources.push((archive.display().to_string(), bytes));
+        }
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    let mut copied: Vec<String> = Vec::new();
+    for (name, bytes) in sources {
+        output::info!("Installing {name} into {}...", prefix.display());
+        for dll in unpack(bytes, &name)? {
+            let file = format!("{}/{}.dll", dll.system_dir, dll.name);
+            let dest = prefix.join("drive_c/windows").join(&file);
+            let builtin = with_suffix(&dest, BUILTIN_SUFFIX);
+            if dest.is_file() && !builtin.exists() {
+                fs::copy(&dest, &builtin)
+                    .with_context(|| format!("Failed to back up {}", dest.display()))?;
+            }
+            fs::write(&dest, &dll.bytes)
+                .with_context(|| format!("Failed to write {}", dest.display()))?;
+            // Recorded as soon as it is written, so a failure below still gets cleaned up.
+            if !copied.contains(&file) {
+                copied.push(file);
+                fs::write(prefix.join(STATE_FILE), copied.join("\n")).context("Failed to write DXVK state file")?;
+            }
+            if !names.contains(&dll.name) {
+                names.push(dll.name);
+            }
+        }
+    }
+
+    for dll in &names {
+        run_reg(runner, prefix, &["add", DLL_OVERRIDES_KEY, "/v", dll, "/d", "native", "/f"])?;
+    }
+    output::success!("DXVK installed ({}).", names.join(", "));
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Puts back the builtin DLL a DXVK one replaced, or deletes the DXVK one when Wine had none.
+fn restore(prefix: &Path, file: &str) -> Result<()> {
+    let dest = prefix.join("drive_c/windows").join(file);
+    let builtin = with_suffix(&dest, BUILTIN_SUFFIX);
+    if builtin.is_file() {
+        fs::rename(&builtin, &dest).with_context(|| format!("Failed to restore {}", dest.display()))
+    } else if dest.is_file() {
+        fs::remove_file(&dest).with_context(|| format!("Failed to remove {}", dest.display()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Puts Wine's own DLLs back, removes the DLL overrides and lets wineboot refresh the prefix.
+pub fn uninstall(runner: &Runner, prefix: &Path) -> Result<()> {
+    let Ok(state) = fs::read_to_string(prefix.join(STATE_FILE)) else {
+        return Ok(());
+    };
+    let mut names: Vec<&str> = Vec::new();
+    for line in state.lines().filter(|line| !line.is_empty()) {
+        // Older state files only have the DLL names, wineboot restores those.
+        let name = match line.split_once('/') {
+            Some((_, file)) => {
+                restore(prefix, line)?;
+                file.trim_end_matches(".dll")
+            }
+            None => line,
+        };
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    for dll in names {
+        // The override may already be gone.
+        let _ = run_reg(runner, prefix, &["delete", DLL_OVERRIDES_KEY, "/v", dll, "/f"]);
+    }
+    // Removed before wineboot, running this again must not delete the restored builtin DLLs.
+    fs::remove_file(prefix.join(STATE_FILE)).context("Failed to remove DXVK state file")?;
+
+    let output = runner
+        .command(prefix)
+        .args(["wineboot", "--update"])
+        .output()
+        .with_kind(ErrorKind::Wine)
+        .context("Failed to run wineboot")?;
+    output::debug!("wineboot --update: {}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    if !output.status.success() {
+        return Err(Error::new(ErrorKind::Wine, format!("wineboot failed:\n{}", String::from_utf8_lossy(&output.stderr))));
+    }
+    output::success!("Switched back to WineD3D.");
+    Ok(())
+}
+
+/// The global prefix and the per-client ones that exist, with the runner each is used with.
+/// Prefixes created later get DXVK when the client is first launched.
+fn prefixes(settings: &Settings) -> Result<Vec<(Runner, PathBuf)>> {
+    let mut prefixes = vec![(settings.runner.clone(), get_wine_prefix()?)];
+    for year in YEARS {
+        let client = clients::resolve(settings, year)?;
+        if !prefixes.iter().any(|(_, prefix)| *prefix == client.prefix) {
+            prefixes.push((client.runner, client.prefix));
+        }
+    }
+    prefixes.retain(|(_, prefix)| prefix.is_dir());
+    Ok(prefixes)
+}
+
+/// `dxvk enable [archives...]`
+pub async fn enable(archives: Vec<PathBuf>) -> Result<()> {
+    let mut settings = Settings::load()?;
+    let prefixes = prefixes(&settings)?;
+    if prefixes.is_empty() {
+        return Err(Error::new(ErrorKind::Wine, "The Wine prefix doesn't exist yet, install the clients first"));
+    }
+
+    for (runner, prefix) in &prefixes {
+        // Archives may have changed, start from a clean prefix.
+        uninstall(runner, prefix)?;
+        install(runner, prefix, &archives).await?;
+    }
+
+    settings.dxvk = true;
+    settings.dxvk_archives = archives;
+    settings.save()
+}
+
+/// `dxvk disable`
+pub fn disable() -> Result<()> {
+    let mut settings = Settings::load()?;
+    for (runner, prefix) in prefixes(&settings)? {
+        uninstall(&runner, &prefix)?;
+    }
+    settings.dxvk = false;
+    settings.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::{restore, with_suffix, BUILTIN_SUFFIX};
+
+    #[test]
+    fn restore_puts_builtins_back_and_removes_the_rest() {
+        let prefix = env::temp_dir().join(format!("openstrap-dxvk-{}", process::id()));
+        let system32 = prefix.join("drive_c/windows/system32");
+        fs::create_dir_all(&system32).unwrap();
+        fs::write(system32.join("d3d9.dll"), "dxvk").unwrap();
+        fs::write(with_suffix(&system32.join("d3d9.dll"), BUILTIN_SUFFIX), "wine").unwrap();
+        fs::write(system32.join("nvapi64.dll"), "dxvk").unwrap();
+
+        restore(&prefix, "system32/d3d9.dll").unwrap();
+        restore(&prefix, "system32/nvapi64.dll").unwrap();
+        restore(&prefix, "system32/missing.dll").unwrap();
+
+        assert_eq!(fs::read_to_string(system32.join("d3d9.dll")).unwrap(), "wine");
+        assert!(!with_suffix(&system32.join("d3d9.dll"), BUILTIN_SUFFIX).exists());
+        assert!(!system32.join("nvapi64.dll").exists());
+        fs::remove_dir_all(prefix).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+//! The error type of the bootstrapper. Each [`ErrorKind`] comes with a suggested fix and
+//! its own exit code, and [`Error::report`] prints the error with a short cause chain.
+use std::{error::Error as StdError, fmt, io};
+
+use crate::{cancel, config::SETUP, output};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+type BoxError = Box<dyn StdError + Send + Sync + 'static>;
+
+/// Causes printed by [`Error::report`] before cutting the chain short.
+const MAX_CAUSES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The server or a download can't be reached.
+    Network,
+    /// A download is incomplete or doesn't match its hash or signature.
+    Integrity,
+    /// A client or DXVK archive can't be extracted.
+    Extraction,
+    /// The URI handler, shortcuts or desktop entries can't be set up.
+    Platform,
+    /// Wine is missing, too old or fails.
+    Wine,
+    /// The client is missing, disabled or crashed.
+    Client,
+    /// Interrupted with Ctrl-C.
+    Cancelled,
+    /// Unknown arguments, clients or settings.
+    Usage,
+    /// Files of the install dir can't be read or written.
+    Io,
+    /// `doctor` found a problem.
+    Doctor,
+}
+
+impl ErrorKind {
+    #[must_use]
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            Self::Usage => 2,
+            Self::Network => 3,
+            Self::Integrity => 4,
+            Self::Extraction => 5,
+            Self::Platform => 6,
+            Self::Wine => 7,
+            Self::Client => 8,
+            Self::Io => 9,
+            Self::Doctor => 10,
+            Self::Cancelled => cancel::EXIT_CODE,
+        }
+    }
+
+    /// What the user can do about it.
+    #[must_use]
+    pub fn fix(self) -> Option<String> {
+        Some(match self {
+            Self::Network => format!("Check your internet connection and that {SETUP} is up, then try again."),
+            Self::Integrity => "Try again. If it keeps failing, the file on the server is broken or was tampered with.".to_string(),
+            Self::Extraction => "Make sure there is enough free disk space, then run `repair all`.".to_string(),
+            Self::Platform => "Run `doctor` to find the missing desktop tool or permission.".to_string(),
+            Self::Wine => "Run `doctor`, then install Wine or pick another runner with `runners set`.".to_string(),
+            Self::Client => "Check the client log with `logs`, and run `repair` if it keeps failing.".to_string(),
+            Self::Io => "Check the permissions and free space of the install directory, `doctor` checks both.".to_string(),
+            // `doctor` already printed a fix for each problem.
+            Self::Cancelled | Self::Usage | Self::Doctor => return None,
+        })
+    }
+}
+
+#[derive(Debug)]
+enum Repr {
+    Message(String),
+    /// A foreign error, shown as it is.
+    Source(BoxError),
+    Context { message: String, source: BoxError },
+}
+
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    repr: Repr,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, repr: Repr::Message(message.into()) }
+    }
+
+    /// Wraps a foreign error, e.g. `.map_err(|e| Error::from_source(ErrorKind::Wine, e))`.
+    pub fn from_source(kind: ErrorKind, source: impl StdError + Send + Sync + 'static) -> Self {
+        Self { kind, repr: Repr::Source(Box::new(source)) }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Reclassifies the error, e.g. an IO error from starting Wine is a Wine error.
+    #[must_use]
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    #[must_use]
+    pub fn context(self, message: impl fmt::Display) -> Self {
+        Self {
+            kind: self.kind,
+            repr: Repr::Context { message: message.to_string(), source: Box::new(self) },
+        }
+    }
+
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        self.kind.exit_code()
+    }
+
+    /// The errors that caused this one, closest first.
+    pub fn causes(&self) -> impl Iterator<Item = &(dyn StdError + 'static)> {
+        std::iter::successors(self.source(), |&cause| cause.source())
+    }
+
+    /// Prints `doing: message`, a few causes and the suggested fix. The run log gets every cause.
+    pub fn report(&self, doing: &str) {
+        output::error!("{doing}: {self}");
+        output::debug!("{:?} error, exit code {}: {self:#}", self.kind, self.exit_code());
+        for (i, cause) in self.causes().enumerate() {
+            if i == MAX_CAUSES {
+                output::info!("  ...");
+                break;
+            }
+            output::info!("  Caused by: {cause}");
+        }
+        if let Some(fix) = self.kind.fix() {
+            output::info!("Fix: {fix}");
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    /// `{:#}` also shows the causes, separated by `: `.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Message(message) | Repr::Context { message, .. } => write!(f, "{message}")?,
+            Repr::Source(source) => write!(f, "{source}")?,
+        }
+        if f.alternate() {
+            for cause in self.causes() {
+                write!(f, ": {cause}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match &self.repr {
+            Repr::Message(_) => None,
+            Repr::Source(source) => source.source(),
+            Repr::Context { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+macro_rules! from_errors {
+    ($($kind:ident: $($source:ty),+;)*) => {
+        $($(
+            impl From<$source> for Error {
+                fn from(source: $source) -> Self {
+                    Self::from_source(ErrorKind::$kind, source)
+                }
+            }
+        )+)*
+    };
+}
+
+from_errors! {
+    Io: io::Error, walkdir::Error, std::env::VarError, std::path::StripPrefixError, serde_json::Error;
+    Network: reqwest::Error;
+    Extraction: zip::result::ZipError;
+    Integrity: hex::FromHexError, ed25519_dalek::SignatureError;
+}
+
+#[cfg(windows)]
+from_errors! {
+    Platform: windows_result::Error;
+}
+
+/// `.context()` for results, keeping the kind of the error.
+pub trait Context<T> {
+    fn context(self, message: impl fmt::Display) -> Result<T>;
+    fn with_context<M: fmt::Display>(self, message: impl FnOnce() -> M) -> Result<T>;
+    fn with_kind(self, kind: ErrorKind) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> Context<T> for std::result::Result<T, E> {
+    fn context(self, message: impl fmt::Display) -> Result<T> {
+        self.map_err(|e| e.into().context(message))
+    }
+
+    fn with_context<M: fmt::Display>(self, message: impl FnOnce() -> M) -> Result<T> {
+        self.map_err(|e| e.into().context(message()))
+    }
+
+    fn with_kind(self, kind: ErrorKind) -> Result<T> {
+        self.map_err(|e| e.into().with_kind(kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error as _, io};
+
+    use super::{Context, Error, ErrorKind};
+    use crate::cancel;
+
+    #[test]
+    fn exit_codes_follow_the_kind() {
+        assert_eq!(Error::new(ErrorKind::Usage, "bad").exit_code(), 2);
+        assert_eq!(Error::new(ErrorKind::Network, "down").exit_code(), 3);
+        assert_eq!(Error::new(ErrorKind::Io, "denied").exit_code(), 9);
+        assert_eq!(Error::new(ErrorKind::Doctor, "broken").exit_code(), 10);
+        assert_eq!(cancel::error().exit_code(), cancel::EXIT_CODE);
+    }
+
+    #[test]
+    fn foreign_errors_get_a_kind() {
+        let err: Error = io::Error::other("denied").into();
+        assert_eq!(err.kind(), ErrorKind::Io);
+        let err: Error = serde_json::from_str::<u32>("{").unwrap_err().into();
+        assert_eq!(err.kind(), ErrorKind::Io);
+        let err = Err::<(), _>(io::Error::other("wine")).with_kind(ErrorKind::Wine).unwrap_err();
+        assert_eq!(err.exit_code(), 7);
+    }
+
+    #[test]
+    fn context_keeps_the_kind_and_the_causes() {
+        let err = Err::<(), _>(io::Error::other("denied"))
+            .context("Failed to write settings file")
+            .context("Failed to save")
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+        assert_eq!(err.to_string(), "Failed to save");
+        assert_eq!(format!("{err:#}"), "Failed to save: Failed to write settings file: denied");
+        assert_eq!(err.causes().count(), 2);
+        assert!(err.source().is_some());
+    }
+}
@@ -0,0 +1,176 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Context, Error, ErrorKind, Result};
+use serde_json::{Map, Value};
+
+use crate::{config::YEARS, settings::Settings, output};
+
+const FLAGS_FILE: &str = "ClientAppSettings.json";
+/// The file the client came with, kept while overrides are written. Empty when it had none.
+const ORIGINAL_FILE: &str = "ClientAppSettings.original.json";
+
+/// Writes the year's flag overrides over the client's own flags to `ClientSettings/ClientAppSettings.json`
+/// of the client folder. Years without overrides get back the file they came with.
+pub fn apply(client_dir: &Path, year: &str) -> Result<()> {
+    let settings = Settings::load()?;
+    let flags = settings.fflags.get(year).filter(|flags| !flags.is_empty());
+    write(&client_dir.join("ClientSettings"), flags)?;
+    if let Some(flags) = flags {
+        output::log!("Applied {} client flags.", flags.len());
+    }
+    Ok(())
+}
+
+fn write(settings_dir: &Path, flags: Option<&Map<String, Value>>) -> Result<()> {
+    let path = settings_dir.join(FLAGS_FILE);
+    let original_path = settings_dir.join(ORIGINAL_FILE);
+
+    let Some(flags) = flags else {
+        if original_path.is_file() {
+            if fs::metadata(&original_path)?.len() == 0 {
+                let _ = fs::remove_file(&path);
+                fs::remove_file(&original_path)?;
+            } else {
+                fs::rename(&original_path, &path).context("Failed to restore ClientAppSettings.json")?;
+            }
+        }
+        return Ok(());
+    };
+
+    fs::create_dir_all(settings_dir)
+        .with_context(|| format!("Failed to create {}", settings_dir.display()))?;
+    if !original_path.is_file() {
+        if path.is_file() {
+            fs::copy(&path, &original_path)
+        } else {
+            fs::write(&original_path, "").map(|()| 0)
+        }
+        .context("Failed to back up ClientAppSettings.json")?;
+    }
+
+    // A client file that isn't a JSON object is replaced rather than merged.
+    let mut merged: Map<String, Value> = fs::read_to_string(&original_path)
+        .ok()
+        .and_then(|original| serde_json::from_str(&original).ok())
+        .unwrap_or_default();
+    merged.extend(flags.iter().map(|(name, value)| (name.clone(), value.clone())));
+    fs::write(&path, serde_json::to_string_pretty(&merged)?).context("Failed to write ClientAppSettings.json")
+}
+
+/// `all` stands for every year.
+fn years(target: &str) -> Result<Vec<String>> {
+    if target == "all" {
+        Ok(YEARS.iter().map(ToString::to_string).collect())
+    } else if YEARS.contains(&target) {
+        Ok(vec![target.to_string()])
+    } else {
+        Err(Error::new(ErrorKind::Usage, format!("Unknown client {target}, available clients: all, {}", YEARS.join(", "))))
+    }
+}
+
+/// `flags [year]`
+pub fn list(target: Option<&str>) -> Result<()> {
+    let settings = Settings::load()?;
+    for year in years(target.unwrap_or("all"))? {
+        let flags = settings.fflags.get(&year).cloned().unwrap_or_default();
+        output::info!("{year}: {} flags", flags.len());
+        for (name, value) in flags {
+            println!("  {name} = {value}");
+        }
+    }
+    Ok(())
+}
+
+/// `flags set <year|all> <flag> <value>`, values are read as JSON and fall back to plain strings.
+pub fn set(target: &str, name: &str, value: &str) -> Result<()> {
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    let mut settings = Settings::load()?;
+    for year in years(target)? {
+        settings
+            .fflags
+            .entry(year)
+            .or_insert_with(Map::new)
+            .insert(name.to_string(), value.clone());
+    }
+    settings.save()?;
+    output::success!("{name} set to {value} for {target}");
+    Ok(())
+}
+
+/// `flags unset <year|all> <flag>`
+pub fn unset(target: &str, name: &str) -> Result<()> {
+    let mut settings = Settings::load()?;
+    for year in years(target)? {
+        if let Some(flags) = settings.fflags.get_mut(&year) {
+            flags.remove(name);
+        }
+    }
+    settings.fflags.retain(|_, flags| !flags.is_empty());
+    settings.save()?;
+    output::success!("{name} removed for {target}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process};
+
+    use serde_json::{json, Map, Value};
+
+    use super::{write, FLAGS_FILE, ORIGINAL_FILE};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("openstrap-flags-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn flags(value: Value) -> Map<String, Value> {
+        let Value::Object(flags) = value else { panic!("flags must be an object") };
+        flags
+    }
+
+    fn read(path: PathBuf) -> Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn overrides_are_merged_and_the_original_restored() {
+        let dir = temp_dir("merge");
+        fs::write(dir.join(FLAGS_FILE), r#"{"FFlagA": true, "DFIntB": 1}"#).unwrap();
+
+        write(&dir, Some(&flags(json!({"DFIntB": 2, "FFlagC": false})))).unwrap();
+        assert_eq!(read(dir.join(FLAGS_FILE)), json!({"FFlagA": true, "DFIntB": 2, "FFlagC": false}));
+
+        write(&dir, Some(&flags(json!({"FFlagC": true})))).unwrap();
+        assert_eq!(read(dir.join(FLAGS_FILE)), json!({"FFlagA": true, "DFIntB": 1, "FFlagC": true}));
+
+        write(&dir, None).unwrap();
+        assert_eq!(read(dir.join(FLAGS_FILE)), json!({"FFlagA": true, "DFIntB": 1}));
+        assert!(!dir.join(ORIGINAL_FILE).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn generated_file_is_removed_without_an_original() {
+        let dir = temp_dir("generated");
+        write(&dir, Some(&flags(json!({"FFlagA": true})))).unwrap();
+        assert_eq!(read(dir.join(FLAGS_FILE)), json!({"FFlagA": true}));
+
+        write(&dir, None).unwrap();
+        assert!(!dir.join(FLAGS_FILE).exists());
+        assert!(!dir.join(ORIGINAL_FILE).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn untouched_client_keeps_its_file() {
+        let dir = temp_dir("untouched");
+        fs::write(dir.join(FLAGS_FILE), "{}").unwrap();
+        write(&dir, None).unwrap();
+        assert_eq!(fs::read_to_string(dir.join(FLAGS_FILE)).unwrap(), "{}");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,66 +1,322 @@
 use std::{
-    process::Command
+    fs::File,
+    io::Read,
+    path::Path,
+    process::ExitStatus,
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
 };
+#[cfg(windows)]
+use std::process::Command;
 
-use anyhow::Result;
+use regex::Regex;
+use tokio::process::{Child, Command as AsyncCommand};
 
-use crate::bootstrapper;
+use crate::error::{Context, Error, ErrorKind, Result};
+
+use crate::{bootstrapper, cancel, flags, lock::InstallLock, logs, mods, output, progress::Progress};
+use crate::{clients, settings::Settings, utils};
+use serde::{Deserialize, Serialize};
 use crate::{
-    config::{URI, URL}
+    config::{LaunchMode, Profile, AUTH_URL, CLIENT_OVERRIDES, LAUNCH_MODES, URI, URL}
 };
 
+/// Built from the `URI` const, so it failing to compile is a bug in the build.
+static URI_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"{URI}:1\+launchmode:([^+]+)\+clientversion:([^+]+)\+gameinfo:([^+]+)\+placelauncherurl:([^+]+)"))
+        .expect("URI pattern should compile")
+});
+
+/// How often `tail` checks the client log for new lines.
+const TAIL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What the bootstrapper does once the client started, `settings set after_launch <close|wait|tail|minimize>`.
+/// Whatever the choice, a failed launch keeps the window open until Enter is pressed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AfterLaunch {
+    /// Close right away, or once the client closed with `watch_client`.
+    #[default]
+    Close,
+    /// Stay open until Enter is pressed.
+    Wait,
+    /// Print the client log as it is written, until the client closes.
+    Tail,
+    /// Minimize the window until the client closes.
+    Minimize,
+}
+
 struct Args {
-    _launch_mode: String,
+    launch_mode: String,
     client_version: String,
     game_info: String,
     place_launcher_url: String,
 }
 
+impl Args {
+    fn placeholder(&self, name: &str, version: &str) -> Option<String> {
+        Some(match name {
+            "auth_url" => AUTH_URL.replace("{url}", URL),
+            "game_info" => self.game_info.clone(),
+            "place_launcher_url" => self.place_launcher_url.clone(),
+            "year" => self.client_version.clone(),
+            "version" => version.to_string(),
+            "url" => URL.to_string(),
+            _ => return None,
+        })
+    }
+
+    /// Fills the placeholders of a launch mode argument template in a single pass, so
+    /// placeholders inside the values (which come from the URI) are left as they are.
+    fn expand(&self, template: &str, version: &str) -> String {
+        let mut expanded = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let found = rest
+                .find('}')
+                .and_then(|end| Some((self.placeholder(&rest[1..end], version)?, end)));
+            if let Some((value, end)) = found {
+                expanded.push_str(&value);
+                rest = &rest[end + 1..];
+            } else {
+                expanded.push('{');
+                rest = &rest[1..];
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+/// Looks up the launch mode for a year, preferring the year's override.
+pub fn find_launch_mode(year: &str, name: &str) -> Result<&'static LaunchMode> {
+    CLIENT_OVERRIDES
+        .iter()
+        .find(|(override_year, mode)| *override_year == year && mode.name == name)
+        .map(|(_, mode)| mode)
+        .or_else(|| LAUNCH_MODES.iter().find(|mode| mode.name == name))
+        .ok_or_else(|| Error::new(ErrorKind::Usage, format!("Unsupported launch mode: {name}")))
+}
+
+/// Waits for the client to close and reports how it went.
+async fn watch(child: &mut Child, log_path: &Path) -> Result<()> {
+    output::log!("Waiting for the client to close...");
+    let started = Instant::now();
+    let status = child.wait().await?;
+    report_exit(status, started, log_path)
+}
+
+/// Prints the client log as it grows until the client closes, then reports how it went.
+async fn tail(child: &mut Child, log_path: &Path) -> Result<()> {
+    output::log!("Showing the client log until the client closes...");
+    let mut log = File::open(log_path)?;
+    let started = Instant::now();
+    let mut pending = Vec::new();
+    let status = loop {
+        // Checked before reading, so the lines written right before exiting are still printed.
+        let status = child.try_wait()?;
+        log.read_to_end(&mut pending)?;
+        while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            output::log!("{}", String::from_utf8_lossy(&line).trim_end());
+        }
+        if let Some(status) = status {
+            break status;
+        }
+        tokio::time::sleep(TAIL_INTERVAL).await;
+    };
+    if !pending.is_empty() {
+        output::log!("{}", String::from_utf8_lossy(&pending).trim_end());
+    }
+    report_exit(status, started, log_path)
+}
+
+fn report_exit(status: ExitStatus, started: Instant, log_path: &Path) -> Result<()> {
+    output::debug!("Client exited with {status}");
+    let runtime = started.elapsed().as_secs();
+    let runtime = format!("{}m{:02}s", runtime / 60, runtime % 60);
+
+    if status.success() {
+        output::success!("Client closed after {runtime}.");
+        return Ok(());
+    }
+    let code = status.code().map_or_else(|| "none".to_string(), |code| code.to_string());
+    output::error!("Client exited with code {code} after {runtime}.");
+    output::info!("Check its log at {}", log_path.display());
+    output::info!("If it keeps crashing, run the bootstrapper with `repair` to reinstall the clients.");
+    Err(Error::new(ErrorKind::Client, "Client crashed"))
+}
+
+/// Starts clients from URIs like `pekora-player:1+launchmode:play+clientversion:2017L+...`,
+/// installing, updating or repairing the client first when needed.
+#[derive(Default)]
+pub struct Launcher {
+    profile: Profile,
+    progress: Option<Arc<dyn Progress>>,
+}
+
+impl Launcher {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The revival whose URIs this launcher starts, see [`Profile`] for what it can't change.
+    #[must_use]
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// Receives the events of this launcher's launches, instead of the default reporter
+    /// printing to the terminal.
+    #[must_use]
+    pub fn progress(mut self, progress: impl Progress + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Starts the client the URI asks for. With `watch_client` set, or `after_launch` set to
+    /// `tail` or `minimize`, waits for it to close.
+    pub async fn launch(&self, uri: &str) -> Result<()> {
+        // Checked before anything gets updated for a link that isn't ours.
+        if !uri.strip_prefix(self.profile.uri).is_some_and(|rest| rest.starts_with(':')) {
+            return Err(Error::new(ErrorKind::Usage, format!("Not a {} URI: {uri}", self.profile.name)));
+        }
+        output::scoped(self.progress.as_ref(), launch(uri)).await
+    }
+}
+
 pub async fn launch(uri: &str) -> Result<()> {
     let (up_to_date, latest_version) = bootstrapper::is_up_to_update().await?;
     if !up_to_date {
-        paris::info!("Out ouf date, updating...");
-        bootstrapper::bootstrap().await?;
+        output::info!("Out ouf date, updating...");
+        bootstrapper::bootstrap(None).await?;
     }
     if !uri.starts_with(&format!("{URI}:")) {
-        anyhow::bail!("Invalid URI");
+        return Err(Error::new(ErrorKind::Usage, "Invalid URI"));
     }
-    let re = regex::Regex::new(&format!(r"{URI}:1\+launchmode:([^+]+)\+clientversion:([^+]+)\+gameinfo:([^+]+)\+placelauncherurl:([^+]+)")).unwrap();
-    let captures = re
+    let captures = URI_PATTERN
         .captures(uri)
-        .ok_or_else(|| anyhow::anyhow!("Invalid URI format"))?;
+        .ok_or_else(|| Error::new(ErrorKind::Usage, "Invalid URI format"))?;
 
     let args: Args = Args {
-        _launch_mode: captures.get(1).unwrap().as_str().to_string(),
-        client_version: captures.get(2).unwrap().as_str().to_string(),
-        game_info: captures.get(3).unwrap().as_str().to_string(),
-        place_launcher_url: captures.get(4).unwrap().as_str().to_string(),
+        launch_mode: captures[1].to_string(),
+        client_version: captures[2].to_string(),
+        game_info: captures[3].to_string(),
+        place_launcher_url: captures[4].to_string(),
     };
-    paris::info!("Starting {}", args.client_version);
+    let mode = find_launch_mode(&args.client_version, &args.launch_mode)?;
+    let settings = Settings::load()?;
+    let client = clients::resolve(&settings, &args.client_version)?;
+    if !client.enabled {
+        return Err(Error::new(ErrorKind::Usage, format!("The {} client is disabled, enable it with `client {} enable`", args.client_version, args.client_version)));
+    }
+    output::info!("Starting {} ({})", args.client_version, mode.name);
     let install_path = bootstrapper::get_install_dir()?;
-    let client_path = install_path.join("Versions").join(&latest_version).join(&args.client_version).join("ProjectXPlayerBeta.exe");
+    let client_dir = install_path.join("Versions").join(&latest_version).join(&args.client_version);
+    // Held until mods and flags are written, so an update or `repair all` can't replace the client folder meanwhile.
+    let lock = InstallLock::acquire().await?;
+    bootstrapper::ensure_client(&latest_version, &args.client_version).await?;
+    let client_path = client_dir.join(mode.executable);
+    if !client_path.is_file() {
+        return Err(Error::new(ErrorKind::Client, format!("{} is not available for {}", mode.executable, args.client_version)));
+    }
+    let mut client_args: Vec<String> = mode.args.iter().map(|arg| args.expand(arg, &latest_version)).collect();
+    client_args.extend(client.args.iter().map(|arg| args.expand(arg, &latest_version)));
+
+    mods::apply(&client_dir, &args.client_version)?;
+    flags::apply(&client_dir, &args.client_version)?;
+    drop(lock);
+    if cancel::is_cancelled() {
+        return Err(cancel::error());
+    }
+
+    output::phase("launch", Some(&args.client_version));
+    let (log_path, log_file) = logs::create_log("client", Some(&args.client_version))?;
+
     #[cfg(windows)]
-    Command::new(client_path)
-        .arg("--play")
-        .arg("-a")
-        .arg(&format!("https://www.{URL}/Login/Negotiate.ashx"))
-        .arg("-t")
-        .arg(args.game_info)
-        .arg("-j")
-        .arg(args.place_launcher_url)
-        .spawn()?;
+    let mut command = Command::new(client_path);
     #[cfg(target_os = "linux")]
-    Command::new("wine")
-        .arg(client_path)
-        .arg("--play")
-        .arg("-a")
-        .arg(&format!("https://www.{URL}/Login/Negotiate.ashx"))
-        .arg("-t")
-        .arg(args.game_info)
-        .arg("-j")
-        .arg(args.place_launcher_url)
-        .spawn()?;
-    
-    paris::success!("Started Client");
+    let mut command = {
+        // `bootstrap` just prepared the global runner and prefix when it updated the clients.
+        if up_to_date || client.runner != settings.runner || client.prefix != bootstrapper::get_wine_prefix()? {
+            bootstrapper::prepare_wine(&client.runner, &client.prefix).await?;
+        }
+        let mut command = client.runner.command(&client.prefix);
+        command.arg(client_path);
+        command
+    };
+    command.args(&client_args).envs(&client.env);
+    output::debug!("Running {command:?}");
+    // Waited on asynchronously, so `watch` works on a current-thread runtime too.
+    let mut child = AsyncCommand::from(command)
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file)
+        .spawn()
+        .with_kind(ErrorKind::Client)
+        .context("Failed to start the client")?;
+
+    output::success!("Started Client");
+    output::log!("Client output is logged to {}", log_path.display());
+
+    match settings.after_launch {
+        AfterLaunch::Tail => tail(&mut child, &log_path).await?,
+        AfterLaunch::Minimize => {
+            utils::minimize_window();
+            watch(&mut child, &log_path).await?;
+        }
+        AfterLaunch::Close | AfterLaunch::Wait if settings.watch_client => watch(&mut child, &log_path).await?,
+        AfterLaunch::Close | AfterLaunch::Wait => {}
+    }
+    output::completed("launch");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Args, URI_PATTERN};
+    use crate::config::{URI, URL};
+
+    fn args(game_info: &str) -> Args {
+        Args {
+            launch_mode: "play".to_string(),
+            client_version: "2017L".to_string(),
+            game_info: game_info.to_string(),
+            place_launcher_url: "https://example.com/{version}".to_string(),
+        }
+    }
+
+    #[test]
+    fn expands_every_placeholder() {
+        let expanded = args("ticket").expand("{year} {version} {url} {game_info} {place_launcher_url}", "v1");
+        assert_eq!(expanded, format!("2017L v1 {URL} ticket https://example.com/{{version}}"));
+    }
+
+    #[test]
+    fn uri_pattern_matches_launch_uris() {
+        let uri = format!("{URI}:1+launchmode:play+clientversion:2017L+gameinfo:ticket+placelauncherurl:https://example.com/");
+        let captures = URI_PATTERN.captures(&uri).unwrap();
+        assert_eq!(&captures[1], "play");
+        assert_eq!(&captures[2], "2017L");
+        assert_eq!(&captures[4], "https://example.com/");
+        assert!(URI_PATTERN.captures(&format!("{URI}:1+launchmode:play")).is_none());
+    }
+
+    #[test]
+    fn auth_url_follows_url() {
+        assert_eq!(args("").expand("{auth_url}", "v1"), format!("https://www.{URL}/Login/Negotiate.ashx"));
+    }
+
+    #[test]
+    fn values_from_the_uri_are_not_expanded_again() {
+        assert_eq!(args("{year}{url}{auth_url}").expand("-t {game_info}", "v1"), "-t {year}{url}{auth_url}");
+    }
+
+    #[test]
+    fn unknown_placeholders_and_braces_are_kept() {
+        assert_eq!(args("").expand("{unknown} {year", "v1"), "{unknown} {year");
+        assert_eq!(args("").expand("{{year}}", "v1"), "{2017L}");
+    }
+}
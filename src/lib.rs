@@ -0,0 +1,61 @@
+//! Installs, updates and launches the clients of a revival, the library behind the
+//! `korone-bootstrapper` binary.
+//!
+//! The revival is picked at build time by the consts in `src/config.rs`, see [`Profile`].
+//! [`Installer`] installs, repairs and uninstalls the clients and [`Launcher`] starts
+//! one from a URI. Both report what they are doing to a [`Progress`]:
+//!
+//! ```no_run
+//! use korone_bootstrapper::{Event, Installer};
+//!
+//! # async fn run() -> korone_bootstrapper::Result<()> {
+//! Installer::new()
+//!     .clients(["2017L", "2021M"])
+//!     .progress(|event: &Event| {
+//!         if let Event::DownloadProgress { label, downloaded, .. } = event {
+//!             println!("{label}: {downloaded} bytes");
+//!         }
+//!     })
+//!     .install()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::missing_errors_doc,
+    reason = "every command can fail on IO or network errors, the error says which"
+)]
+
+mod bootstrapper;
+mod cancel;
+pub mod cli;
+mod clients;
+mod config;
+mod doctor;
+#[cfg(target_os = "linux")]
+mod dxvk;
+mod error;
+mod flags;
+mod launcher;
+mod lock;
+mod logs;
+mod mods;
+mod output;
+#[cfg(target_os = "linux")]
+mod prerequisites;
+pub mod progress;
+mod self_update;
+mod settings;
+mod status;
+mod uninstall;
+mod utils;
+#[cfg(target_os = "linux")]
+mod wine;
+
+pub use bootstrapper::Installer;
+pub use config::Profile;
+pub use error::{Error, ErrorKind, Result};
+pub use launcher::Launcher;
+pub use output::{Event, Level};
+pub use progress::Progress;
@@ -0,0 +1,75 @@
+use std::{
+    fs::{self, File, TryLockError},
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use crate::error::{Context, Result};
+
+use crate::{bootstrapper::get_install_dir, cancel, config::NAME, output};
+
+/// How often a waiting bootstrapper checks whether the lock was released.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+static HELD: AtomicBool = AtomicBool::new(false);
+
+/// Whether this bootstrapper currently holds the install lock.
+pub fn is_held() -> bool {
+    HELD.load(Ordering::SeqCst)
+}
+
+pub fn get_lock_path() -> Result<PathBuf> {
+    Ok(get_install_dir()?.join(".lock"))
+}
+
+/// Exclusive lock on the install dir, held while installing, updating or
+/// uninstalling and released when dropped.
+pub struct InstallLock {
+    _file: File,
+}
+
+impl InstallLock {
+    /// Takes the lock, waiting for another running bootstrapper to release it.
+    pub async fn acquire() -> Result<Self> {
+        let install_dir = get_install_dir()?;
+        fs::create_dir_all(&install_dir)
+            .with_context(|| format!("Failed to create install dir {}", install_dir.display()))?;
+        let path = get_lock_path()?;
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+
+        // Polled rather than blocking on `file.lock()`, which would stall the runtime.
+        let mut waiting = false;
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) => {
+                    if !waiting {
+                        output::warn!("Another {NAME} install or update is running, waiting for it to finish...");
+                        waiting = true;
+                    }
+                    tokio::select! {
+                        () = tokio::time::sleep(POLL_INTERVAL) => {}
+                        () = cancel::cancelled() => return Err(cancel::error()),
+                    }
+                }
+                Err(TryLockError::Error(e)) => {
+                    return Err(e).context("Failed to lock the install dir");
+                }
+            }
+        }
+        HELD.store(true, Ordering::SeqCst);
+        Ok(Self { _file: file })
+    }
+}
+
+impl Drop for InstallLock {
+    fn drop(&mut self) {
+        HELD.store(false, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+use crate::error::{Context, Error, ErrorKind, Result};
+
+use crate::{bootstrapper::get_install_dir, config::MAX_LOGS, output};
+
+pub fn get_logs_dir() -> Result<PathBuf> {
+    Ok(get_install_dir()?.join("Logs"))
+}
+
+/// Log files starting with `kind`, oldest first.
+fn list_logs(kind: &str) -> Result<Vec<PathBuf>> {
+    let Ok(entries) = fs::read_dir(get_logs_dir()?) else {
+        return Ok(Vec::new());
+    };
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "log")
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&format!("{kind}-")))
+        })
+        .collect();
+    // Names start with the kind followed by a sortable timestamp.
+    logs.sort();
+    Ok(logs)
+}
+
+/// Creates `Logs/<kind>-<timestamp>[-<label>].log`, removing the oldest logs of `kind` above `MAX_LOGS`.
+pub fn create_log(kind: &str, label: Option<&str>) -> Result<(PathBuf, File)> {
+    let logs_dir = get_logs_dir()?;
+    fs::create_dir_all(&logs_dir)
+        .with_context(|| format!("Failed to create logs dir {}", logs_dir.display()))?;
+
+    // Two clients started in the same millisecond must not share (and truncate) a log, the second one waits for the next.
+    let (path, file) = loop {
+        let timestamp = chrono::Local::now().format("%Y%m%dT%H%M%S%.3f");
+        let name = match label {
+            Some(label) => format!("{kind}-{timestamp}-{label}.log"),
+            None => format!("{kind}-{timestamp}.log"),
+        };
+        let path = logs_dir.join(name);
+        match File::create_new(&path) {
+            Ok(file) => break (path, file),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => thread::sleep(Duration::from_millis(1)),
+            Err(e) => return Err(Error::from(e).context(format!("Failed to create {}", path.display()))),
+        }
+    };
+
+    let logs = list_logs(kind)?;
+    for old in logs.iter().take(logs.len().saturating_sub(MAX_LOGS)) {
+        let _ = fs::remove_file(old);
+    }
+    Ok((path, file))
+}
+
+/// Starts `Logs/bootstrapper-<timestamp>.log`, getting everything reported from now on.
+pub fn start_run_log() -> Result<PathBuf> {
+    let (path, file) = create_log("bootstrapper", None)?;
+    output::set_run_log(file);
+    Ok(path)
+}
+
+pub fn latest_log(kind: &str) -> Result<Option<PathBuf>> {
+    Ok(list_logs(kind)?.pop())
+}
+
+/// `logs [open]`, prints or opens the latest client log.
+pub fn main(open: bool) -> Result<()> {
+    let path = latest_log("client")?.ok_or_else(|| Error::new(ErrorKind::Usage, "No client log yet"))?;
+    if open {
+        open::that(&path)?;
+    } else {
+        output::info!("{}", path.display());
+        print!("{}", fs::read_to_string(&path)?);
+    }
+    Ok(())
+}
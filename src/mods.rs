@@ -0,0 +1,196 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::{Context, Error, ErrorKind, Result};
+use walkdir::WalkDir;
+
+use crate::{bootstrapper::get_install_dir, config::YEARS, settings::Settings, output};
+
+/// Files of the client replaced by mods are moved here.
+const ORIGINALS_DIR: &str = ".openstrap-originals";
+/// Lists the files the mods were copied to, one per line.
+const MANIFEST_FILE: &str = ".openstrap-mods";
+
+pub fn get_mods_dir() -> Result<PathBuf> {
+    Ok(get_install_dir()?.join("Modifications"))
+}
+
+/// Puts back the files replaced by the mods and removes the ones they added.
+pub fn restore(client_dir: &Path) -> Result<()> {
+    let Ok(manifest) = fs::read_to_string(client_dir.join(MANIFEST_FILE)) else {
+        return Ok(());
+    };
+    let originals = client_dir.join(ORIGINALS_DIR);
+
+    for rel in manifest.lines().filter(|line| !line.is_empty()) {
+        let target = client_dir.join(rel);
+        let _ = fs::remove_file(&target);
+        let original = originals.join(rel);
+        if original.is_file() {
+            fs::rename(&original, &target)
+                .with_context(|| format!("Failed to restore {}", target.display()))?;
+        }
+    }
+
+    if originals.is_dir() {
+        fs::remove_dir_all(&originals).context("Failed to remove the originals backup")?;
+    }
+    fs::remove_file(client_dir.join(MANIFEST_FILE)).context("Failed to remove the mods manifest")
+}
+
+/// Copies `Modifications/<year>` over the client, keeping the files it replaces.
+pub fn apply(client_dir: &Path, year: &str) -> Result<()> {
+    // Start from the original files, mods may have been removed since last time.
+    restore(client_dir)?;
+
+    let mods_dir = get_mods_dir()?.join(year);
+    if !mods_dir.is_dir() || !Settings::load()?.mods {
+        return Ok(());
+    }
+    copy_mods(client_dir, &mods_dir)
+}
+
+fn copy_mods(client_dir: &Path, mods_dir: &Path) -> Result<()> {
+    let originals = client_dir.join(ORIGINALS_DIR);
+    let mut applied: Vec<String> = Vec::new();
+    for entry in WalkDir::new(mods_dir).sort_by_file_name().into_iter().filter_map(Result::ok) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(mods_dir)?;
+        let target = client_dir.join(rel);
+
+        if target.is_file() {
+            let original = originals.join(rel);
+            if let Some(parent) = original.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&target, &original)
+                .with_context(|| format!("Failed to back up {}", target.display()))?;
+        } else if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Recorded as soon as the original is moved away, so `restore` puts it back if a later file fails.
+        applied.push(rel.to_string_lossy().into_owned());
+        fs::write(client_dir.join(MANIFEST_FILE), applied.join("\n")).context("Failed to write the mods manifest")?;
+        fs::copy(entry.path(), &target)
+            .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+    }
+
+    if !applied.is_empty() {
+        output::log!("Applied {} modified files.", applied.len());
+    }
+    Ok(())
+}
+
+fn installed_client_dirs() -> Result<Vec<(String, PathBuf)>> {
+    let install_dir = get_install_dir()?;
+    let version = fs::read_to_string(install_dir.join("version"))
+        .map_err(|_| Error::new(ErrorKind::Client, "No client is installed"))?;
+    Ok(YEARS
+        .iter()
+        .map(|year| (year.to_string(), install_dir.join("Versions").join(version.trim()).join(year)))
+        .filter(|(_, dir)| dir.is_dir())
+        .collect())
+}
+
+/// `mods`, creates the `Modifications/<year>` folders and shows where they are.
+pub fn show() -> Result<()> {
+    let mods_dir = get_mods_dir()?;
+    for year in YEARS {
+        fs::create_dir_all(mods_dir.join(year))?;
+    }
+    output::info!("Put your modified files in {}", mods_dir.display());
+    output::info!("e.g. {} replaces content/fonts/arial.ttf of the 2017L client", mods_dir.join("2017L/content/fonts/arial.ttf").display());
+    Ok(())
+}
+
+/// `mods apply` / `mods restore`, turns the mods on or off for every installed client.
+pub fn set_enabled(enabled: bool) -> Result<()> {
+    let mut settings = Settings::load()?;
+    settings.mods = enabled;
+    settings.save()?;
+
+    for (year, client_dir) in installed_client_dirs()? {
+        if enabled {
+            apply(&client_dir, &year)?;
+        } else {
+            restore(&client_dir)?;
+        }
+    }
+    if enabled {
+        output::success!("Modifications applied.");
+    } else {
+        output::success!("Original client files restored, run `mods apply` to use your modifications again.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process};
+
+    use super::{copy_mods, restore, MANIFEST_FILE, ORIGINALS_DIR};
+
+    /// A client and a mods folder next to it.
+    fn temp_dirs(name: &str) -> (PathBuf, PathBuf) {
+        let dir = env::temp_dir().join(format!("openstrap-mods-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("client/content")).unwrap();
+        fs::create_dir_all(dir.join("mods/content")).unwrap();
+        (dir.join("client"), dir.join("mods"))
+    }
+
+    fn read(path: PathBuf) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn mods_are_applied_and_the_originals_restored() {
+        let (client, mods) = temp_dirs("apply");
+        fs::write(client.join("content/arial.ttf"), "original").unwrap();
+        fs::write(mods.join("content/arial.ttf"), "modded").unwrap();
+        fs::write(mods.join("content/extra.txt"), "added").unwrap();
+
+        copy_mods(&client, &mods).unwrap();
+        assert_eq!(read(client.join("content/arial.ttf")), "modded");
+        assert_eq!(read(client.join("content/extra.txt")), "added");
+        assert_eq!(read(client.join(ORIGINALS_DIR).join("content/arial.ttf")), "original");
+
+        restore(&client).unwrap();
+        assert_eq!(read(client.join("content/arial.ttf")), "original");
+        assert!(!client.join("content/extra.txt").exists());
+        assert!(!client.join(ORIGINALS_DIR).exists());
+        assert!(!client.join(MANIFEST_FILE).exists());
+        fs::remove_dir_all(client.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn failed_apply_can_still_be_restored() {
+        let (client, mods) = temp_dirs("failed");
+        fs::write(client.join("content/arial.ttf"), "original").unwrap();
+        fs::write(mods.join("content/arial.ttf"), "modded").unwrap();
+        // `textures` is a file in the client, so the mod's `textures/sky.png` can't be copied.
+        fs::write(client.join("textures"), "original").unwrap();
+        fs::create_dir_all(mods.join("textures")).unwrap();
+        fs::write(mods.join("textures/sky.png"), "modded").unwrap();
+
+        assert!(copy_mods(&client, &mods).is_err());
+        restore(&client).unwrap();
+        assert_eq!(read(client.join("content/arial.ttf")), "original");
+        assert_eq!(read(client.join("textures")), "original");
+        assert!(!client.join(ORIGINALS_DIR).exists());
+        fs::remove_dir_all(client.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn restore_without_mods_does_nothing() {
+        let (client, _) = temp_dirs("none");
+        fs::write(client.join("content/arial.ttf"), "original").unwrap();
+        restore(&client).unwrap();
+        assert_eq!(read(client.join("content/arial.ttf")), "original");
+        fs::remove_dir_all(client.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,245 @@
+//! Everything the bootstrapper reports goes through here as an [`Event`], passed to the
+//! active [`Progress`] reporter: the terminal one by default, the JSON one with `--output json`,
+//! or whatever library users set. Events and [`debug!`] lines also go to the run log, if one is set.
+use std::{
+    fs::File,
+    io::Write,
+    sync::{Arc, LazyLock, Mutex, RwLock},
+};
+
+use serde::Serialize;
+
+use crate::progress::{Progress, TerminalReporter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Log,
+    Info,
+    Success,
+    Warn,
+    Error,
+}
+
+impl Level {
+    const fn tag(self) -> &'static str {
+        match self {
+            Self::Log => "LOG",
+            Self::Info => "INFO",
+            Self::Success => "SUCCESS",
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+        }
+    }
+}
+
+/// What `--output json` prints, tagged by `event`, e.g.
+/// `{"event":"download_progress","label":"2017L","downloaded":1024,"total":4096}`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    PhaseStarted {
+        phase: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        client: Option<&'a str>,
+    },
+    DownloadProgress {
+        label: &'a str,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    ExtractionProgress {
+        label: &'a str,
+        extracted: u64,
+        total: u64,
+    },
+    /// Files and folders deleted by `uninstall`.
+    RemovalProgress {
+        path: &'a str,
+        removed: u64,
+        total: u64,
+    },
+    Message {
+        level: Level,
+        message: &'a str,
+    },
+    Warning {
+        message: &'a str,
+    },
+    Error {
+        message: &'a str,
+    },
+    Completed {
+        phase: &'a str,
+    },
+}
+
+static REPORTER: LazyLock<RwLock<Arc<dyn Progress>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(TerminalReporter::default())));
+
+tokio::task_local! {
+    /// The reporter of the `Installer` or `Launcher` call running in this task, if it has one.
+    static SCOPED: Arc<dyn Progress>;
+}
+
+/// Runs `future` with its events going to `progress`, or to the global reporter without one.
+/// Other calls, even running at the same time, keep their own reporter.
+pub(crate) async fn scoped<F: Future>(progress: Option<&Arc<dyn Progress>>, future: F) -> F::Output {
+    match progress {
+        Some(progress) => SCOPED.scope(Arc::clone(progress), future).await,
+        None => future.await,
+    }
+}
+
+/// Sends every following event to `progress` instead of the terminal, for the whole process,
+/// e.g. with `--output json`. Reporters given to `Installer::progress` and `Launcher::progress`
+/// take precedence during their calls.
+pub fn set_progress(progress: Arc<dyn Progress>) {
+    if let Ok(mut reporter) = REPORTER.write() {
+        *reporter = progress;
+    }
+}
+
+static RUN_LOG: Mutex<Option<File>> = Mutex::new(None);
+
+/// Also writes every following event and debug line to `file`, see [`logs::start_run_log`](crate::logs::start_run_log).
+pub fn set_run_log(file: File) {
+    if let Ok(mut run_log) = RUN_LOG.lock() {
+        *run_log = Some(file);
+    }
+}
+
+fn write_run_log(tag: &str, message: &str) {
+    if let Ok(mut run_log) = RUN_LOG.lock()
+        && let Some(file) = run_log.as_mut()
+    {
+        let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        let _ = writeln!(file, "{time} {tag:<7} {message}");
+    }
+}
+
+/// Only the ends of progress are logged, the rest is on screen.
+fn log_event(event: &Event) {
+    match *event {
+        Event::PhaseStarted { phase, client: Some(client) } => write_run_log("PHASE", &format!("{phase} {client}")),
+        Event::PhaseStarted { phase, client: None } => write_run_log("PHASE", phase),
+        Event::DownloadProgress { label, downloaded: 0, total } => write_run_log(
+            "DEBUG",
+            &format!("Downloading {label}, {}", total.map_or_else(|| "unknown size".to_string(), |total| format!("{total} bytes"))),
+        ),
+        Event::DownloadProgress { label, downloaded, total: Some(total) } if downloaded == total => {
+            write_run_log("DEBUG", &format!("Downloaded {label}, {downloaded} bytes"));
+        }
+        Event::ExtractionProgress { label, extracted, total } if extracted == total => {
+            write_run_log("DEBUG", &format!("Extracted {total} files of {label}"));
+        }
+        Event::RemovalProgress { removed, total, .. } if removed == total => {
+            write_run_log("DEBUG", &format!("Removed {total} files"));
+        }
+        Event::DownloadProgress { .. } | Event::ExtractionProgress { .. } | Event::RemovalProgress { .. } => {}
+        Event::Message { level, message } => write_run_log(level.tag(), message),
+        Event::Warning { message } => write_run_log(Level::Warn.tag(), message),
+        Event::Error { message } => write_run_log(Level::Error.tag(), message),
+        Event::Completed { phase } => write_run_log("DONE", phase),
+    }
+}
+
+pub fn emit(event: &Event) {
+    log_event(event);
+    // Cloned out so a reporter printing messages can't deadlock with `set_progress`.
+    let reporter = SCOPED
+        .try_with(Arc::clone)
+        .ok()
+        .or_else(|| REPORTER.read().ok().map(|reporter| Arc::clone(&reporter)));
+    if let Some(reporter) = reporter {
+        reporter.on_event(event);
+    }
+}
+
+/// Progress is reported once per percent, or per MiB without a total, to keep the JSON output short.
+pub(crate) fn should_report(before: u64, after: u64, total: Option<u64>) -> bool {
+    let step = total.map_or(1024 * 1024, |total| (total / 100).max(1));
+    before / step != after / step || Some(after) == total
+}
+
+pub fn phase(phase: &str, client: Option<&str>) {
+    emit(&Event::PhaseStarted { phase, client });
+}
+
+pub fn completed(phase: &str) {
+    emit(&Event::Completed { phase });
+}
+
+/// Reports a message. Use the macros below.
+pub fn print(level: Level, message: &str) {
+    emit(&match level {
+        Level::Warn => Event::Warning { message },
+        Level::Error => Event::Error { message },
+        level => Event::Message { level, message },
+    });
+}
+
+/// Writes detail only worth reading after a failure, like URLs and status codes, to the run log.
+/// Use [`debug!`].
+pub fn debug(message: &str) {
+    write_run_log("DEBUG", message);
+}
+
+// Defined under prefixed names and used as `output::info!` and so on, a plain `warn`
+// would clash with the built-in attribute.
+macro_rules! __output_debug {
+    ($($arg:tt)*) => { $crate::output::debug(&format!($($arg)*)) };
+}
+
+macro_rules! __output_log {
+    ($($arg:tt)*) => { $crate::output::print($crate::output::Level::Log, &format!($($arg)*)) };
+}
+
+macro_rules! __output_info {
+    ($($arg:tt)*) => { $crate::output::print($crate::output::Level::Info, &format!($($arg)*)) };
+}
+
+macro_rules! __output_success {
+    ($($arg:tt)*) => { $crate::output::print($crate::output::Level::Success, &format!($($arg)*)) };
+}
+
+macro_rules! __output_warn {
+    ($($arg:tt)*) => { $crate::output::print($crate::output::Level::Warn, &format!($($arg)*)) };
+}
+
+macro_rules! __output_error {
+    ($($arg:tt)*) => { $crate::output::print($crate::output::Level::Error, &format!($($arg)*)) };
+}
+
+pub(crate) use {
+    __output_debug as debug, __output_error as error, __output_info as info, __output_log as log, __output_success as success,
+    __output_warn as warn,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::should_report;
+
+    const MIB: u64 = 1024 * 1024;
+
+    #[test]
+    fn reports_once_per_percent() {
+        assert!(!should_report(0, 99, Some(10_000)));
+        assert!(should_report(99, 100, Some(10_000)));
+        assert!(!should_report(100, 150, Some(10_000)));
+        assert!(should_report(150, 450, Some(10_000)));
+    }
+
+    #[test]
+    fn reports_completion() {
+        assert!(should_report(9_990, 10_000, Some(10_000)));
+        assert!(should_report(0, 50, Some(50)));
+    }
+
+    #[test]
+    fn reports_once_per_mib_without_total() {
+        assert!(!should_report(0, MIB - 1, None));
+        assert!(should_report(MIB - 1, MIB, None));
+        assert!(!should_report(MIB, 2 * MIB - 1, None));
+    }
+}
@@ -0,0 +1,86 @@
+use std::{fs, path::Path, process::Output};
+
+use crate::error::{Context, Error, ErrorKind, Result};
+
+use crate::{
+    bootstrapper::{build_client, download_with_retry},
+    config::{Prerequisite, WINE_PREREQUISITES},
+    output,
+    wine::{find_in_path, Runner},
+};
+
+/// Lists the prerequisites already installed into the prefix, one per line.
+const STATE_FILE: &str = ".openstrap-prerequisites";
+
+fn installed(prefix: &Path) -> Vec<String> {
+    fs::read_to_string(prefix.join(STATE_FILE))
+        .map(|state| state.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Output goes to the run log rather than the terminal, which may be carrying `--output json`.
+fn check_output(output: &Output, name: &str) -> Result<()> {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    output::debug!("Installing {name}: {}{stderr}", String::from_utf8_lossy(&output.stdout));
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::new(ErrorKind::Wine, format!("Installing {name} failed ({}):\n{}", output.status, stderr.trim())))
+    }
+}
+
+async fn install_one(runner: &Runner, prefix: &Path, prerequisite: &Prerequisite) -> Result<()> {
+    if let Some(winetricks) = find_in_path("winetricks") {
+        let output = runner
+            .tool_command(winetricks, prefix)
+            .args(["-q", prerequisite.name])
+            .output()
+            .with_kind(ErrorKind::Wine)
+            .context("Failed to run winetricks")?;
+        return check_output(&output, prerequisite.name);
+    }
+
+    let Some(url) = prerequisite.redistributable else {
+        return Err(Error::new(ErrorKind::Wine, format!("{} can only be installed with winetricks, please install it", prerequisite.name)));
+    };
+    let bytes = download_with_retry(&build_client()?, url, prerequisite.name).await?;
+    let installer = prefix.join("drive_c").join(format!("{}-setup.exe", prerequisite.name));
+    fs::write(&installer, bytes)
+        .with_context(|| format!("Failed to write {}", installer.display()))?;
+
+    let output = runner
+        .command(prefix)
+        .arg(&installer)
+        .args(prerequisite.args)
+        .output()
+        .with_kind(ErrorKind::Wine)
+        .context("Failed to run the redistributable");
+    let _ = fs::remove_file(&installer);
+    check_output(&output?, prerequisite.name)
+}
+
+/// Installs the profile's `WINE_PREREQUISITES` that aren't in the prefix yet.
+pub async fn install(runner: &Runner, prefix: &Path) -> Result<()> {
+    let mut done = installed(prefix);
+
+    for prerequisite in WINE_PREREQUISITES {
+        if done.iter().any(|name| name == prerequisite.name) {
+            continue;
+        }
+        // Not recorded as done, so it is installed once winetricks is.
+        if prerequisite.redistributable.is_none() && find_in_path("winetricks").is_none() {
+            output::warn!("Skipping {}, it can only be installed with winetricks, which isn't installed.", prerequisite.name);
+            continue;
+        }
+        output::log!("Installing {} into the Wine prefix...", prerequisite.name);
+        install_one(runner, prefix, prerequisite)
+            .await
+            .with_context(|| format!("Failed to install {}", prerequisite.name))?;
+
+        done.push(prerequisite.name.to_string());
+        fs::write(prefix.join(STATE_FILE), done.join("\n"))
+            .context("Failed to write prerequisites state file")?;
+        output::success!("Installed {}.", prerequisite.name);
+    }
+    Ok(())
+}
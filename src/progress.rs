@@ -0,0 +1,135 @@
+//! Reporters showing the [`Event`]s of the bootstrapper.
+use std::sync::Mutex;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::output::{Event, Level};
+
+/// Receives the events of the bootstrapper, set with [`Installer::progress`](crate::Installer::progress)
+/// or [`Launcher::progress`](crate::Launcher::progress).
+/// Closures taking an `&Event` implement it.
+pub trait Progress: Send + Sync {
+    fn on_event(&self, event: &Event);
+}
+
+impl<F: Fn(&Event) + Send + Sync> Progress for F {
+    fn on_event(&self, event: &Event) {
+        self(event);
+    }
+}
+
+const DOWNLOAD_TEMPLATE: &str =
+    "{msg}\n{percent_precise}% |{bar}| {bytes}/{total_bytes} [{elapsed_precise}<{eta_precise}, {decimal_bytes_per_sec}]\n";
+const COUNT_TEMPLATE: &str = "{msg}\n{percent}% |{bar}| {human_pos}/{human_len} [{elapsed_precise}<{eta_precise}, {per_sec}]\n";
+
+/// What the bar currently drawn shows, a new bar is started when it changes.
+#[derive(PartialEq)]
+enum Task {
+    Download(String),
+    Extraction(String),
+    Removal,
+}
+
+/// Log lines with `paris` and progress bars with `indicatif`, the default.
+#[derive(Default)]
+pub struct TerminalReporter {
+    bar: Mutex<Option<(Task, ProgressBar)>>,
+}
+
+impl TerminalReporter {
+    fn update(&self, task: Task, position: u64, total: Option<u64>, message: impl FnOnce() -> String) {
+        let Ok(mut current) = self.bar.lock() else {
+            return;
+        };
+        if current.as_ref().is_none_or(|(drawn, _)| *drawn != task) {
+            if let Some((_, bar)) = current.take() {
+                bar.finish();
+            }
+            let template = if matches!(task, Task::Download(_)) { DOWNLOAD_TEMPLATE } else { COUNT_TEMPLATE };
+            let bar = ProgressBar::new(total.unwrap_or(0));
+            if let Ok(style) = ProgressStyle::default_bar().template(template) {
+                bar.set_style(style.progress_chars("█▌ "));
+            }
+            *current = Some((task, bar));
+        }
+
+        let Some((task, bar)) = current.as_ref() else {
+            return;
+        };
+        bar.set_message(message());
+        if let Some(total) = total {
+            bar.set_length(total);
+        }
+        bar.set_position(position);
+        if Some(position) == total {
+            // Downloads are followed by their extraction, only keep the latter on screen.
+            if matches!(task, Task::Download(_)) {
+                bar.finish_and_clear();
+            } else {
+                bar.finish();
+            }
+            *current = None;
+        }
+    }
+
+    fn print(&self, level: Level, message: &str) {
+        let print = || match level {
+            Level::Log => paris::log!("{message}"),
+            Level::Info => paris::info!("{message}"),
+            Level::Success => paris::success!("{message}"),
+            Level::Warn => paris::warn!("{message}"),
+            Level::Error => paris::error!("{message}"),
+        };
+        match self.bar.lock().as_deref() {
+            Ok(Some((_, bar))) => bar.suspend(print),
+            _ => print(),
+        }
+    }
+}
+
+impl Progress for TerminalReporter {
+    fn on_event(&self, event: &Event) {
+        match *event {
+            Event::DownloadProgress { label, downloaded, total } => {
+                self.update(Task::Download(label.to_string()), downloaded, total, || format!("Downloading {label}..."));
+            }
+            Event::ExtractionProgress { label, extracted, total } => {
+                self.update(Task::Extraction(label.to_string()), extracted, Some(total), || {
+                    if extracted == total { format!("Extracted {label} client.") } else { "Extracting...".to_string() }
+                });
+            }
+            Event::RemovalProgress { path, removed, total } => {
+                self.update(Task::Removal, removed, Some(total), || format!("Removing {path}..."));
+            }
+            Event::Message { level, message } => self.print(level, message),
+            Event::Warning { message } => self.print(Level::Warn, message),
+            Event::Error { message } => self.print(Level::Error, message),
+            // A phase ending before its bar got to the end, e.g. files left out of `uninstall`.
+            Event::PhaseStarted { .. } | Event::Completed { .. } => {
+                if let Ok(mut current) = self.bar.lock()
+                    && let Some((_, bar)) = current.take()
+                {
+                    bar.finish();
+                }
+            }
+        }
+    }
+}
+
+/// One JSON event per line on stdout, for `--output json`.
+pub struct JsonReporter;
+
+impl Progress for JsonReporter {
+    fn on_event(&self, event: &Event) {
+        if let Ok(line) = serde_json::to_string(event) {
+            println!("{line}");
+        }
+    }
+}
+
+/// Shows nothing.
+pub struct SilentReporter;
+
+impl Progress for SilentReporter {
+    fn on_event(&self, _: &Event) {}
+}
@@ -0,0 +1,101 @@
+use std::{env, fs};
+
+use crate::error::{Context, Error, ErrorKind, Result};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bootstrapper::{build_client, download_with_retry},
+    config::{SELF_UPDATE_PUBLIC_KEY, SELF_UPDATE_URL},
+    output,
+    settings::Settings,
+};
+
+/// What `SELF_UPDATE_URL/<os>.json` returns for the latest bootstrapper release.
+#[derive(Deserialize)]
+struct Release {
+    version: String,
+    url: String,
+    /// Hex encoded SHA-256 of the binary.
+    sha256: String,
+    /// Hex encoded ed25519 signature of the binary, made with the key matching `SELF_UPDATE_PUBLIC_KEY`.
+    signature: String,
+}
+
+/// Compares dotted versions like `0.1.10` and `0.1.9` number by number.
+fn is_newer(version: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(version) > parse(current)
+}
+
+fn verify(release: &Release, bytes: &[u8]) -> Result<()> {
+    let digest = hex::encode(Sha256::digest(bytes));
+    if !digest.eq_ignore_ascii_case(&release.sha256) {
+        return Err(Error::new(ErrorKind::Integrity, format!("Hash mismatch: got {digest}, expected {}", release.sha256)));
+    }
+
+    let key: [u8; 32] = hex::decode(SELF_UPDATE_PUBLIC_KEY)?
+        .try_into()
+        .map_err(|_| Error::new(ErrorKind::Integrity, "SELF_UPDATE_PUBLIC_KEY is not a 32 byte key"))?;
+    let signature: [u8; 64] = hex::decode(&release.signature)?
+        .try_into()
+        .map_err(|_| Error::new(ErrorKind::Integrity, "The release signature is not 64 bytes long"))?;
+    VerifyingKey::from_bytes(&key)
+        .context("Invalid SELF_UPDATE_PUBLIC_KEY")?
+        .verify_strict(bytes, &Signature::from_bytes(&signature))
+        .context("The release signature is invalid")
+}
+
+async fn update() -> Result<()> {
+    let client = build_client()?;
+    let url = format!("{SELF_UPDATE_URL}/{}.json", env::consts::OS);
+    let release: Release = client
+        .get(&url)
+        .send()
+        .await
+        .inspect(|res| output::debug!("GET {url}: {}", res.status()))
+        .context("Failed to reach the bootstrapper update endpoint")?
+        .error_for_status()
+        .context("Bootstrapper update endpoint returned error")?
+        .json()
+        .await
+        .context("Failed to read the bootstrapper release")?;
+
+    let current = env!("CARGO_PKG_VERSION");
+    if !is_newer(&release.version, current) {
+        return Ok(());
+    }
+
+    output::info!("Updating the bootstrapper from {current} to {}...", release.version);
+    let bytes = download_with_retry(&client, &release.url, "bootstrapper").await?;
+    verify(&release, &bytes)?;
+
+    // Written next to the running binary so the replacement stays on the same filesystem.
+    let current_exe = env::current_exe()?;
+    let new_exe = current_exe.with_extension("new");
+    fs::write(&new_exe, &bytes).context("Failed to write the new bootstrapper")?;
+    let replaced = self_replace::self_replace(&new_exe).context("Failed to replace the bootstrapper");
+    let _ = fs::remove_file(&new_exe);
+    replaced?;
+
+    output::success!("Bootstrapper updated to {}, it will be used from the next run.", release.version);
+    Ok(())
+}
+
+/// Checks for a new bootstrapper release, unless turned off with `settings set self_update false`.
+/// Failures are only reported, an outdated bootstrapper still works.
+pub async fn check() {
+    if SELF_UPDATE_URL.is_empty() || SELF_UPDATE_PUBLIC_KEY.is_empty() || !Settings::load().is_ok_and(|settings| settings.self_update) {
+        return;
+    }
+    if let Err(err) = update().await {
+        output::warn!("Could not update the bootstrapper: {err}");
+        output::debug!("Self-update failed: {err:#}");
+    }
+}
@@ -0,0 +1,147 @@
+use std::{collections::BTreeMap, fs};
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+
+use crate::error::{Context, Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{bootstrapper::get_install_dir, clients::ClientSettings, launcher::AfterLaunch, output};
+
+#[cfg(target_os = "linux")]
+use crate::wine::Runner;
+
+/// User settings, stored as `settings.json` in the install dir.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools, reason = "each one is an independent toggle")]
+pub struct Settings {
+    /// Keep the bootstrapper open while the client runs and report how it exited.
+    pub watch_client: bool,
+    /// What to do once a client started from a URI.
+    pub after_launch: AfterLaunch,
+    /// Check for new bootstrapper releases at startup.
+    pub self_update: bool,
+    /// Copy `Modifications/<year>` over the clients.
+    pub mods: bool,
+    /// Client flags written to `ClientAppSettings.json`, by year.
+    pub fflags: BTreeMap<String, Map<String, Value>>,
+    /// Launch overrides, by year.
+    pub clients: BTreeMap<String, ClientSettings>,
+    #[cfg(target_os = "linux")]
+    pub runner: Runner,
+    /// Install DXVK into the prefix instead of using `WineD3D`.
+    #[cfg(target_os = "linux")]
+    pub dxvk: bool,
+    /// Local archives to take DXVK from, `DXVK_URLS` is used when empty.
+    #[cfg(target_os = "linux")]
+    pub dxvk_archives: Vec<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            watch_client: false,
+            after_launch: AfterLaunch::default(),
+            self_update: true,
+            mods: true,
+            fflags: BTreeMap::new(),
+            clients: BTreeMap::new(),
+            #[cfg(target_os = "linux")]
+            runner: Runner::default(),
+            #[cfg(target_os = "linux")]
+            dxvk: false,
+            #[cfg(target_os = "linux")]
+            dxvk_archives: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Result<Self> {
+        let path = get_install_dir()?.join("settings.json");
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_kind(ErrorKind::Io)
+                .with_context(|| format!("Failed to parse {}", path.display())),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Changes one setting, values are read as JSON and fall back to plain strings.
+    pub fn with_value(self, key: &str, value: &str) -> Result<Self> {
+        let mut json = serde_json::to_value(self)?;
+        let field = json
+            .get_mut(key)
+            .ok_or_else(|| Error::new(ErrorKind::Usage, format!("Unknown setting {key}")))?;
+        *field = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+
+        serde_json::from_value(json)
+            .with_kind(ErrorKind::Usage)
+            .with_context(|| format!("Invalid value for {key}: {value}"))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let install_dir = get_install_dir()?;
+        fs::create_dir_all(&install_dir)
+            .with_context(|| format!("Failed to create install dir {}", install_dir.display()))?;
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(install_dir.join("settings.json"), content).context("Failed to write settings file")
+    }
+}
+
+/// `settings`
+pub fn show() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&Settings::load()?)?);
+    Ok(())
+}
+
+/// `settings set <key> <value>`
+pub fn set(key: &str, value: &str) -> Result<()> {
+    let settings = Settings::load()?.with_value(key, value)?;
+    settings.save()?;
+    output::success!("{key} set to {value}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Settings;
+    use crate::{error::ErrorKind, launcher::AfterLaunch};
+
+    #[test]
+    fn values_are_parsed_as_json_or_strings() {
+        let settings = Settings::default()
+            .with_value("watch_client", "true")
+            .and_then(|settings| settings.with_value("after_launch", "tail"))
+            .and_then(|settings| settings.with_value("fflags", r#"{"2017L": {"FFlagA": true}}"#))
+            .unwrap();
+        assert!(settings.watch_client);
+        assert_eq!(settings.after_launch, AfterLaunch::Tail);
+        assert_eq!(settings.fflags["2017L"]["FFlagA"], json!(true));
+    }
+
+    #[test]
+    fn unknown_keys_and_invalid_values_are_usage_errors() {
+        let err = Settings::default().with_value("nope", "true").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Usage);
+        let err = Settings::default().with_value("after_launch", "explode").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Usage);
+        let err = Settings::default().with_value("watch_client", "5").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Usage);
+    }
+
+    #[test]
+    fn missing_fields_use_the_defaults() {
+        let settings: Settings = serde_json::from_value(json!({"mods": false})).unwrap();
+        assert!(!settings.mods);
+        assert!(settings.self_update);
+        assert_eq!(settings.after_launch, AfterLaunch::Close);
+
+        let saved = serde_json::to_value(&settings).unwrap();
+        let loaded: Settings = serde_json::from_value(saved.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), saved);
+    }
+}
@@ -0,0 +1,183 @@
+use std::{fs, path::Path};
+
+use crate::error::Result;
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::{
+    bootstrapper::{build_client, fetch_latest_version, get_install_dir, get_launcher_path, installed_clients},
+    clients,
+    config::{NAME, URI, URL, YEARS},
+    output,
+    settings::Settings,
+    utils,
+};
+
+#[derive(Serialize)]
+struct ClientStatus {
+    year: String,
+    installed: bool,
+    enabled: bool,
+    size: u64,
+}
+
+#[derive(Serialize)]
+struct UriHandlerStatus {
+    command: Option<String>,
+    points_to_launcher: bool,
+}
+
+#[derive(Serialize)]
+struct WineStatus {
+    runner: String,
+    prefix: String,
+    prefix_exists: bool,
+}
+
+#[derive(Serialize)]
+struct Status {
+    profile: String,
+    url: String,
+    install_dir: String,
+    installed: bool,
+    active_version: Option<String>,
+    installed_versions: Vec<String>,
+    latest_version: Option<String>,
+    clients: Vec<ClientStatus>,
+    disk_usage: u64,
+    uri_handler: UriHandlerStatus,
+    wine: Option<WineStatus>,
+}
+
+fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(fs::Metadata::is_file)
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[allow(clippy::cast_precision_loss, reason = "only used for display")]
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+async fn collect() -> Result<Status> {
+    let install_dir = get_install_dir()?;
+    let settings = Settings::load()?;
+    let active_version = fs::read_to_string(install_dir.join("version"))
+        .ok()
+        .map(|version| version.trim().to_string());
+
+    let mut installed_versions: Vec<String> = fs::read_dir(install_dir.join("Versions"))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    installed_versions.sort();
+
+    let recorded = installed_clients(&install_dir);
+    let clients = YEARS
+        .iter()
+        .map(|year| {
+            let client_dir = active_version
+                .as_ref()
+                .map(|version| install_dir.join("Versions").join(version).join(year));
+            let installed = recorded.iter().any(|y| y == year) && client_dir.as_ref().is_some_and(|dir| dir.is_dir());
+            ClientStatus {
+                year: year.to_string(),
+                installed,
+                enabled: clients::is_enabled(&settings, year),
+                size: client_dir.filter(|_| installed).map_or(0, |dir| dir_size(&dir)),
+            }
+        })
+        .collect();
+
+    let command = utils::get_uri_handler(URI);
+    let launcher_path = get_launcher_path()?;
+    let uri_handler = UriHandlerStatus {
+        points_to_launcher: command
+            .as_ref()
+            .is_some_and(|command| command.contains(&*launcher_path.to_string_lossy())),
+        command,
+    };
+
+    #[cfg(target_os = "linux")]
+    let wine = {
+        let prefix = crate::bootstrapper::get_wine_prefix()?;
+        Some(WineStatus {
+            runner: settings.runner.to_string(),
+            prefix_exists: prefix.join("system.reg").is_file(),
+            prefix: prefix.display().to_string(),
+        })
+    };
+    #[cfg(windows)]
+    let wine = None;
+
+    let latest_version = match build_client() {
+        Ok(client) => fetch_latest_version(&client).await.ok(),
+        Err(_) => None,
+    };
+
+    Ok(Status {
+        profile: NAME.to_string(),
+        url: format!("https://www.{URL}/"),
+        installed: active_version.is_some(),
+        install_dir: install_dir.display().to_string(),
+        active_version,
+        installed_versions,
+        latest_version,
+        clients,
+        disk_usage: if install_dir.is_dir() { dir_size(&install_dir) } else { 0 },
+        uri_handler,
+        wine,
+    })
+}
+
+/// `status [--json]`
+pub async fn main(json: bool) -> Result<()> {
+    let status = collect().await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+    println!("Profile:          {} ({})", status.profile, status.url);
+    println!("Install dir:      {}", status.install_dir);
+    println!("Active version:   {}", or_none(&status.active_version));
+    println!("Latest version:   {}", status.latest_version.clone().unwrap_or_else(|| "unreachable".to_string()));
+    println!("Versions on disk: {}", status.installed_versions.join(", "));
+    println!("Disk usage:       {}", human_size(status.disk_usage));
+    println!("Clients:");
+    for client in &status.clients {
+        let state = match (client.installed, client.enabled) {
+            (_, false) => "disabled".to_string(),
+            (true, true) => format!("installed, {}", human_size(client.size)),
+            (false, true) => "not installed".to_string(),
+        };
+        println!("  {}: {state}", client.year);
+    }
+    println!("URI handler:      {}", or_none(&status.uri_handler.command));
+    if status.uri_handler.command.is_some() && !status.uri_handler.points_to_launcher {
+        output::warn!("The {URI} URI doesn't point to the installed launcher, run the bootstrapper again to fix it.");
+    }
+    if let Some(wine) = &status.wine {
+        println!("Wine runner:      {}", wine.runner);
+        println!("Wine prefix:      {}{}", wine.prefix, if wine.prefix_exists { "" } else { " (not created)" });
+    }
+    Ok(())
+}
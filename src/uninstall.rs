@@ -12,119 +12,173 @@ use std::os::windows::process::CommandExt;
 #[cfg(target_os = "linux")]
 use self_replace::self_delete;
 
-use anyhow::Result;
+use crate::error::Result;
 
 use walkdir::WalkDir;
-use indicatif::{ProgressBar, ProgressStyle};
 use crate::{
-    bootstrapper::*, config::*, utils
+    bootstrapper::get_install_dir, cancel, config::{NAME, URI}, lock::{get_lock_path, InstallLock},
+    output::{self, Event},
+    utils,
 };
 
+/// Prompts go to stderr with `--output json`, which keeps stdout for the JSON events.
+fn prompt(json: bool, text: &str) -> Result<()> {
+    if json {
+        eprint!("{text}");
+        io::stderr().flush()?;
+    } else {
+        print!("{text}");
+        io::stdout().flush()?;
+    }
+    Ok(())
+}
+
+fn ask(json: bool, question: &str) -> Result<bool> {
+    let mut option: String = String::new();
+    prompt(json, &format!("{question} (y/N): "))?;
+    io::stdin().read_line(&mut option)?;
 
-pub async fn main() -> Result<()> {
+    let option = option.trim().to_lowercase();
+    Ok(option == "yes" || option == "y")
+}
+
+/// Removes the install dir (but the Wine prefix with `keep_prefix`), the URI handler and the
+/// uninstall shortcut without asking anything. Returns whether the running binary is the
+/// installed one, which is deleted on Linux and left for the caller to delete on Windows.
+pub async fn remove(keep_prefix: bool) -> Result<bool> {
     let install_dir = get_install_dir()?;
     let current_exe = env::current_exe()?;
     let mut uninstall_from_boostrapper_installer = true; // i.e. running the binary outside the installation folder.
 
-    if install_dir.is_dir(){
-        let mut option: String = String::new();
-        print!("You are about to uninstall {}.\nAre you sure to continue? (y/N): ", NAME);
-        io::stdout().flush()?;
-        io::stdin()
-            .read_line(&mut option)
-            .expect("Failed to read line");
+    #[cfg(target_os = "linux")]
+    let kept_prefix = Some(crate::bootstrapper::get_wine_prefix()?).filter(|prefix| keep_prefix && prefix.is_dir());
+    #[cfg(windows)]
+    let kept_prefix: Option<std::path::PathBuf> = {
+        let _ = keep_prefix;
+        None
+    };
 
-        let option = option.trim().to_lowercase();
-        if option == "yes" || option == "y"{
-            paris::info!("Starting...");
+    let lock = InstallLock::acquire().await?;
+    let lock_path = get_lock_path()?;
+    output::phase("uninstall", None);
+    output::info!("Starting...");
 
-            let total_files_dir = WalkDir::new(&install_dir)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .count();
+    let is_kept = |path: &std::path::Path| kept_prefix.as_ref().is_some_and(|prefix| path.starts_with(prefix));
 
-            paris::info!("Removing {} files and directories...", total_files_dir);
+    let total_files_dir = WalkDir::new(&install_dir)
+        .into_iter()
+        .filter_entry(|e| !is_kept(e.path()))
+        .filter_map(Result::ok)
+        .count();
 
-            let pb = ProgressBar::new(total_files_dir as u64);
-            pb.set_style(ProgressStyle::default_bar()
-                .template("{msg}\n{percent}% |{bar}| {human_pos}/{human_len} [{elapsed_precise}<{eta_precise}, {per_sec}]\n")?
-                .progress_chars("█▌ "));
+    output::info!("Removing {} files and directories...", total_files_dir);
 
-            let mut removed = 0;
+    let total = total_files_dir as u64;
+    let mut removed = 0;
 
-            for entry in WalkDir::new(&install_dir).contents_first(true) {
-                let entry = entry?;
-                let path = entry.path();
+    for entry in WalkDir::new(&install_dir).contents_first(true).into_iter().filter_entry(|e| !is_kept(e.path())) {
+        if cancel::is_cancelled() {
+            return Err(cancel::error());
+        }
+        let entry = entry?;
+        let path = entry.path();
 
-                if path == current_exe {
-                    uninstall_from_boostrapper_installer = false;
-                    continue;
-                }
+        if path == current_exe {
+            uninstall_from_boostrapper_installer = false;
+            continue;
+        }
+        if path == lock_path {
+            continue;
+        }
 
-                let res = if entry.file_type().is_dir() {
-                    fs::remove_dir(path)
-                } else {
-                    fs::remove_file(path)
-                };
-
-                match res {
-                    Ok(_) => {
-                        removed += 1;
-                        pb.set_position(removed);
-                        pb.set_message(format!("Removing {:?}...", path));
-                    }
-                    Err(e) => {
-                        if path == install_dir {
-                            continue;
-                        }
-                        paris::error!("Failed to remove {path:?} ({e})");
-                    }
+        let res = if entry.file_type().is_dir() {
+            fs::remove_dir(path)
+        } else {
+            fs::remove_file(path)
+        };
+
+        match res {
+            Ok(()) => {
+                removed += 1;
+                if output::should_report(removed - 1, removed, Some(total)) {
+                    let path = path.display().to_string();
+                    output::emit(&Event::RemovalProgress { path: &path, removed, total });
                 }
             }
+            Err(e) => {
+                if path == install_dir {
+                    continue;
+                }
+                output::error!("Failed to remove {path:?} ({e})");
+            }
+        }
+    }
 
-            pb.finish();
+    drop(lock);
+    let _ = fs::remove_file(&lock_path);
+    let _ = fs::remove_dir(&install_dir);
 
-            paris::success!("Successfully removing {} clients", NAME);
+    output::success!("Successfully removing {} clients", NAME);
+    if let Some(prefix) = &kept_prefix {
+        output::info!("Kept the Wine prefix at {}", prefix.display());
+    }
 
-            paris::log!("Removing URI...");
+    output::phase("unregister", None);
+    output::log!("Removing URI...");
 
-            if let Err(e) = utils::remove_uri(URI) {
-                paris::warn!("Failed to remove URI {}", e);
-            } else {
-                paris::success!("URI removed.");
-            }
+    if let Err(e) = utils::remove_uri(URI) {
+        output::warn!("Failed to remove URI {}", e);
+    } else {
+        output::success!("URI removed.");
+    }
 
-            paris::log!("Removing uninstall shortcut...");
+    output::log!("Removing uninstall shortcut...");
 
-            if let Err(e) = utils::remove_uninstall_shortcut() {
-                paris::warn!("Failed to remove shortcut {}", e);
-            } else {
-                paris::success!("Shortcut removed.");
-            }
+    if let Err(e) = utils::remove_uninstall_shortcut() {
+        output::warn!("Failed to remove shortcut {}", e);
+    } else {
+        output::success!("Shortcut removed.");
+    }
 
-            #[cfg(target_os = "linux")]
-            if !uninstall_from_boostrapper_installer{
-                self_delete()?;
-            }
+    #[cfg(target_os = "linux")]
+    if !uninstall_from_boostrapper_installer{
+        self_delete()?;
+    }
 
-            paris::success!("{} is uninstalled.",NAME);
-            print!("Press Enter to continue...");
-            io::stdout().flush().unwrap();
-            io::stdin().read_line(&mut String::new()).expect(&String::new());
+    output::success!("{} is uninstalled.",NAME);
+    output::completed("uninstall");
+    Ok(!uninstall_from_boostrapper_installer)
+}
 
-            #[cfg(windows)]
-            if !uninstall_from_boostrapper_installer{
-                let _ = Command::new("cmd")
-                    .raw_arg(format!(" /C ping 127.0.0.1 -n 3 > nul & del \"{}\" & rmdir \"{}\"", current_exe.display(), install_dir.display()))
-                    .spawn();
-            }
-            return Ok(());
-        } else {
-            paris::info!("Aborted.");
-            return Ok(());
-        }
-    } else {
-        paris::success!("{} client already uninstalled, no need to worry.", NAME)
+/// `uninstall`, asks for confirmation first.
+pub async fn main(json: bool) -> Result<()> {
+    let install_dir = get_install_dir()?;
+    if !install_dir.is_dir(){
+        output::success!("{NAME} client already uninstalled, no need to worry.");
+        return Ok(());
+    }
+
+    if !ask(json, &format!("You are about to uninstall {NAME}.\nAre you sure to continue?"))? {
+        output::info!("Aborted.");
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    let keep_prefix = crate::bootstrapper::get_wine_prefix()?.is_dir()
+        && ask(json, "Keep the Wine prefix (saved settings and installed dependencies)?")?;
+    #[cfg(windows)]
+    let keep_prefix = false;
+
+    #[cfg_attr(target_os = "linux", allow(unused_variables, reason = "the binary already deleted itself"))]
+    let ran_from_install_dir = remove(keep_prefix).await?;
+    prompt(json, "Press Enter to continue...")?;
+    io::stdin().read_line(&mut String::new())?;
+
+    #[cfg(windows)]
+    if ran_from_install_dir {
+        let _ = Command::new("cmd")
+            .raw_arg(format!(" /C ping 127.0.0.1 -n 3 > nul & del \"{}\" & rmdir \"{}\"", env::current_exe()?.display(), install_dir.display()))
+            .spawn();
     }
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,6 +1,12 @@
 use std::path::Path;
-use anyhow::Result;
-use crate::config::*;
+#[cfg(windows)]
+use crate::config::{AUTHOR, URL};
+use crate::config::NAME;
+use crate::error::Result;
+#[cfg(target_os = "linux")]
+use crate::{error::{Context, ErrorKind}, output};
+#[cfg(target_os = "linux")]
+use crate::config::DESKTOP_ENTRY_VERSION;
 #[cfg(windows)]
 use windows_registry::{Value, CURRENT_USER};
 
@@ -13,7 +19,17 @@ use std::{
     fs,
 };
 
-pub fn register_uri(uri_scheme: &str, exe_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+/// Refreshes the desktop entries cache. Only a warning when it fails, the entries work without it on most desktops.
+#[cfg(target_os = "linux")]
+fn update_desktop_database(applications: &Path) {
+    match Command::new("update-desktop-database").arg(applications).output() {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => output::warn!("update-desktop-database failed: {}", String::from_utf8_lossy(&output.stderr).trim()),
+        Err(e) => output::warn!("Could not run update-desktop-database ({e}), install desktop-file-utils if links don't open."),
+    }
+}
+
+pub fn register_uri(uri_scheme: &str, exe_path: &Path) -> Result<()> {
     #[cfg(windows)]{
         let classes_root = CURRENT_USER.create(r"Software\Classes")?;
         let scheme_key = classes_root.create(uri_scheme)?;
@@ -31,8 +47,10 @@ pub fn register_uri(uri_scheme: &str, exe_path: &Path) -> Result<(), Box<dyn std
 
 
         let home_dir = env::var("HOME")?;
-        let applications = PathBuf::from(home_dir).join(format!(".local/share/applications/"));
+        let applications = PathBuf::from(home_dir).join(".local/share/applications/");
+        fs::create_dir_all(&applications)?;
 
+    #[allow(clippy::unnecessary_debug_formatting)] // Debug output quotes the path for Exec=
     let entry_content = format!("[Desktop Entry]
 Name={NAME}
 Exec={exe_path:?} %u
@@ -41,28 +59,29 @@ Version={DESKTOP_ENTRY_VERSION}
 Terminal=true
 MimeType=x-scheme-handler/{uri_scheme}
 ");
-        fs::write(&applications.join(format!("{NAME}.desktop")),entry_content)?;
-        fs::set_permissions(&applications.join(format!("{NAME}.desktop")), fs::Permissions::from_mode(0o755))?;
+        fs::write(applications.join(format!("{NAME}.desktop")),entry_content)?;
+        fs::set_permissions(applications.join(format!("{NAME}.desktop")), fs::Permissions::from_mode(0o755))?;
 
 
-        let _ = Command::new("update-desktop-database")
-            .arg(&applications)
-            .output()
-            .expect("Failed to update desktop database");
+        update_desktop_database(&applications);
 
-        let _ = Command::new("xdg-settings")
+        let output = Command::new("xdg-settings")
             .arg("set")
             .arg("default-url-scheme-handler")
             .arg(uri_scheme)
-            .arg(&applications.join(format!("{NAME}.desktop")))
+            .arg(applications.join(format!("{NAME}.desktop")))
             .output()
-            .expect("Failed to set default url scheme");
+            .with_kind(ErrorKind::Platform)
+            .context("Failed to run xdg-settings, is xdg-utils installed?")?;
+        if !output.status.success() {
+            output::warn!("xdg-settings failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
 
         Ok(())
     }
 }
 
-pub fn add_uninstall_shortcut(exe_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+pub fn add_uninstall_shortcut(exe_path: &Path) -> Result<()> {
     #[cfg(windows)]{
         let classes_root = CURRENT_USER.create(r"Software\Microsoft\Windows\CurrentVersion\Uninstall")?;
         let app_key = classes_root.create(NAME)?;
@@ -76,8 +95,9 @@ pub fn add_uninstall_shortcut(exe_path: &Path) -> Result<(), Box<dyn std::error:
     }
     #[cfg(target_os = "linux")]{
         let home_dir = env::var("HOME")?;
-        let applications = PathBuf::from(home_dir).join(format!(".local/share/applications/"));
+        let applications = PathBuf::from(home_dir).join(".local/share/applications/");
         
+        #[allow(clippy::unnecessary_debug_formatting)] // Debug output quotes the path for Exec=
         let entry_content = format!("[Desktop Entry]
 Name=Uninstal {NAME}
 Exec={exe_path:?} uninstall
@@ -85,18 +105,45 @@ Type=Application
 Terminal=true
 Version={DESKTOP_ENTRY_VERSION}
 ");
-        fs::write(&applications.join(format!("{NAME}-Uninstall.desktop")),entry_content)?;
-        fs::set_permissions(&applications.join(format!("{NAME}.desktop")), fs::Permissions::from_mode(0o755))?;
+        fs::write(applications.join(format!("{NAME}-Uninstall.desktop")),entry_content)?;
+        fs::set_permissions(applications.join(format!("{NAME}.desktop")), fs::Permissions::from_mode(0o755))?;
 
-        let _ = Command::new("update-desktop-database")
-            .arg(&applications)
-            .output()
-            .expect("Failed to update desktop database");
+        update_desktop_database(&applications);
     }
     Ok(())
 }
 
-pub fn remove_uri(uri_scheme: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// The command registered for the URI scheme, if any.
+pub fn get_uri_handler(uri_scheme: &str) -> Option<String> {
+    #[cfg(windows)]{
+        CURRENT_USER
+            .open(format!(r"Software\Classes\{uri_scheme}\shell\open\command"))
+            .and_then(|key| key.get_string(""))
+            .ok()
+    }
+    #[cfg(target_os = "linux")]{
+        let output = Command::new("xdg-mime")
+            .arg("query")
+            .arg("default")
+            .arg(format!("x-scheme-handler/{uri_scheme}"))
+            .output()
+            .ok()?;
+        let desktop_file = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if desktop_file.is_empty() {
+            return None;
+        }
+        let home_dir = env::var("HOME").ok()?;
+        let entry = fs::read_to_string(PathBuf::from(home_dir).join(".local/share/applications/").join(&desktop_file)).ok();
+        Some(
+            entry
+                .as_deref()
+                .and_then(|entry| entry.lines().find_map(|line| line.strip_prefix("Exec=")))
+                .map_or(desktop_file.clone(), String::from),
+        )
+    }
+}
+
+pub fn remove_uri(uri_scheme: &str) -> Result<()> {
     #[cfg(windows)]{
         let classes_root = CURRENT_USER.open(r"Software\Classes")?;
         let _ = classes_root.remove_tree(uri_scheme);
@@ -104,29 +151,48 @@ pub fn remove_uri(uri_scheme: &str) -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(target_os = "linux")]{
         let _ = uri_scheme; // avoid warnings :P
         let home_dir = env::var("HOME")?;
-        let applications = PathBuf::from(home_dir).join(format!(".local/share/applications/"));
+        let applications = PathBuf::from(home_dir).join(".local/share/applications/");
         let _ = fs::remove_file(applications.join(format!("{NAME}.desktop")));
-        let _ = Command::new("update-desktop-database")
-            .arg(&applications)
-            .output()
-            .expect("Failed to update desktop database");
+        update_desktop_database(&applications);
     }
     Ok(())
 }
 
-pub fn remove_uninstall_shortcut() -> Result<(), Box<dyn std::error::Error>> {
+pub fn remove_uninstall_shortcut() -> Result<()> {
     #[cfg(windows)]{
         let classes_root = CURRENT_USER.create(r"Software\Microsoft\Windows\CurrentVersion\Uninstall")?;
         let _ = classes_root.remove_tree(NAME)?;
     }#[cfg(target_os = "linux")]{
         let home_dir = env::var("HOME")?;
-        let applications = PathBuf::from(home_dir).join(format!(".local/share/applications/"));
+        let applications = PathBuf::from(home_dir).join(".local/share/applications/");
         let _ = fs::remove_file(applications.join(format!("{NAME}-Uninstall.desktop")));
-        let _ = Command::new("update-desktop-database")
-            .arg(&applications)
-            .output()
-            .expect("Failed to update desktop database");
+        update_desktop_database(&applications);
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Minimizes the console window the bootstrapper runs in, if there is one.
+#[cfg(windows)]
+pub fn minimize_window() {
+    use windows_sys::Win32::{System::Console::GetConsoleWindow, UI::WindowsAndMessaging::{ShowWindow, SW_MINIMIZE}};
+    // SAFETY: both calls only take a window handle, a null one (no console) is ignored.
+    unsafe {
+        let window = GetConsoleWindow();
+        if !window.is_null() {
+            ShowWindow(window, SW_MINIMIZE);
+        }
+    }
+}
+
+/// Asks the terminal to minimize itself, with the xterm escape sequence most terminals understand.
+#[cfg(target_os = "linux")]
+pub fn minimize_window() {
+    use std::io::{IsTerminal, Write};
+
+    let mut stdout = std::io::stdout();
+    if stdout.is_terminal() {
+        let _ = write!(stdout, "\x1b[2t");
+        let _ = stdout.flush();
+    }
+}
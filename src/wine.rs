@@ -0,0 +1,224 @@
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fmt,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::error::{Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+use crate::output;
+
+/// The Wine used to run the clients.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "path", rename_all = "lowercase")]
+pub enum Runner {
+    /// `wine` from PATH.
+    #[default]
+    System,
+    /// A Wine build folder, the one containing `bin/wine`.
+    Wine(PathBuf),
+    /// A Proton or Proton-GE folder, the one containing the `proton` script.
+    Proton(PathBuf),
+}
+
+impl Runner {
+    /// Detects what kind of runner a folder (or a `wine` binary) is.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let path = if path.is_file() {
+            path.parent()
+                .filter(|bin| bin.ends_with("bin"))
+                .and_then(Path::parent)
+                .ok_or_else(|| Error::new(ErrorKind::Usage, format!("{} is not inside a Wine build", path.display())))?
+        } else {
+            path
+        };
+
+        if path.join("proton").is_file() && proton_files(path).is_some() {
+            Ok(Self::Proton(path.to_path_buf()))
+        } else if path.join("bin/wine").is_file() {
+            Ok(Self::Wine(path.to_path_buf()))
+        } else {
+            Err(Error::new(ErrorKind::Usage, format!("{} is neither a Wine build nor a Proton folder", path.display())))
+        }
+    }
+
+    #[must_use]
+    pub fn wine_binary(&self) -> PathBuf {
+        match self {
+            Self::System => PathBuf::from("wine"),
+            Self::Wine(dir) => dir.join("bin/wine"),
+            Self::Proton(dir) => proton_files(dir)
+                .unwrap_or_else(|| dir.join("files"))
+                .join("bin/wine"),
+        }
+    }
+
+    #[must_use]
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::System => find_in_path("wine").is_some(),
+            _ => self.wine_binary().is_file(),
+        }
+    }
+
+    /// A `wine` command running in `prefix`, with the environment the runner needs.
+    #[must_use]
+    pub fn command(&self, prefix: &Path) -> Command {
+        self.tool_command(self.wine_binary(), prefix)
+    }
+
+    /// A command for a Wine tool like winetricks, set up to use this runner.
+    pub fn tool_command(&self, program: impl AsRef<OsStr>, prefix: &Path) -> Command {
+        let mut command = Command::new(program);
+        command.env("WINEPREFIX", prefix);
+        if *self != Self::System {
+            command.env("WINE", self.wine_binary());
+        }
+
+        if let Self::Proton(dir) = self {
+            // Proton's Wine isn't meant to be run on its own, point it at its bundled libraries.
+            let files = proton_files(dir).unwrap_or_else(|| dir.join("files"));
+            command.env(
+                "WINEDLLPATH",
+                join_paths([files.join("lib64/wine"), files.join("lib/wine")], None),
+            );
+            command.env(
+                "LD_LIBRARY_PATH",
+                join_paths([files.join("lib64"), files.join("lib")], env::var_os("LD_LIBRARY_PATH")),
+            );
+        }
+
+        command
+    }
+}
+
+impl fmt::Display for Runner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::System => write!(f, "System Wine"),
+            Self::Wine(dir) => write!(f, "Wine ({})", dir.display()),
+            Self::Proton(dir) => write!(f, "Proton ({})", dir.display()),
+        }
+    }
+}
+
+/// Proton keeps its Wine in `files/` (newer and GE builds) or `dist/` (older builds).
+fn proton_files(dir: &Path) -> Option<PathBuf> {
+    ["files", "dist"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|files| files.join("bin/wine").is_file())
+}
+
+fn join_paths<const N: usize>(paths: [PathBuf; N], rest: Option<OsString>) -> OsString {
+    let mut joined: Vec<PathBuf> = paths.into();
+    if let Some(rest) = rest {
+        joined.extend(env::split_paths(&rest));
+    }
+    env::join_paths(joined).unwrap_or_default()
+}
+
+/// Reads the major and minor version out of `wine --version`,
+/// e.g. `wine-9.0 (Staging)` or `wine-8.0-rc1`.
+pub fn parse_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.trim().strip_prefix("wine-")?;
+    let mut numbers = version
+        .split(|c: char| !c.is_ascii_digit())
+        .map(str::parse::<u32>);
+    let major = numbers.next()?.ok()?;
+    let minor = numbers.next().and_then(Result::ok).unwrap_or(0);
+    Some((major, minor))
+}
+
+#[must_use]
+pub fn find_in_path(binary: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(binary))
+        .find(|path| path.is_file())
+}
+
+/// Folders where Lutris, Bottles and Steam keep their Wine and Proton builds.
+fn runner_locations() -> Vec<PathBuf> {
+    let Ok(home) = env::var("HOME") else {
+        return Vec::new();
+    };
+    let home = PathBuf::from(home);
+    vec![
+        home.join(".local/share/lutris/runners/wine"),
+        home.join(".local/share/bottles/runners"),
+        home.join(".var/app/com.usebottles.bottles/data/bottles/runners"),
+        home.join(".steam/root/compatibilitytools.d"),
+        home.join(".local/share/Steam/compatibilitytools.d"),
+        home.join(".var/app/com.valvesoftware.Steam/data/Steam/compatibilitytools.d"),
+        home.join(".steam/root/steamapps/common"),
+        home.join(".local/share/Steam/steamapps/common"),
+        PathBuf::from("/usr/share/steam/compatibilitytools.d"),
+    ]
+}
+
+/// Lists the runners installed on this machine.
+pub fn discover() -> Vec<Runner> {
+    let mut runners = Vec::new();
+    if Runner::System.is_available() {
+        runners.push(Runner::System);
+    }
+
+    for location in runner_locations() {
+        let Ok(entries) = fs::read_dir(&location) else {
+            continue;
+        };
+        let mut found: Vec<Runner> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| Runner::from_path(&entry.path()).ok())
+            .filter(|runner| !runners.contains(runner))
+            .collect();
+        found.sort_by_key(ToString::to_string);
+        runners.extend(found);
+    }
+
+    runners
+}
+
+pub fn list_runners() -> Result<()> {
+    let selected = Settings::load()?.runner;
+    let runners = discover();
+
+    if runners.is_empty() {
+        output::warn!("No Wine runner found, install Wine or pass the path of one to `runners set`.");
+    }
+    for (i, runner) in runners.iter().enumerate() {
+        let marker = if *runner == selected { "*" } else { " " };
+        println!("{marker} {}. {runner}", i + 1);
+    }
+    if !runners.contains(&selected) {
+        println!("* {selected} (not found)");
+    }
+    Ok(())
+}
+
+/// Finds a runner by `system`, its number in `runners list` or its path.
+pub fn parse_runner_choice(choice: &str) -> Result<Runner> {
+    if choice == "system" {
+        Ok(Runner::System)
+    } else if let Ok(index) = choice.parse::<usize>() {
+        discover()
+            .into_iter()
+            .nth(index.wrapping_sub(1))
+            .ok_or_else(|| Error::new(ErrorKind::Usage, format!("No runner numbered {index}, see `runners list`")))
+    } else {
+        Runner::from_path(Path::new(choice))
+    }
+}
+
+pub fn set_runner(choice: &str) -> Result<()> {
+    let mut settings = Settings::load()?;
+    settings.runner = parse_runner_choice(choice)?;
+    settings.save()?;
+    output::success!("Now using {}", settings.runner);
+    Ok(())
+}